@@ -0,0 +1,133 @@
+//BallController: a ball that rolls down slopes, bounces off walls and goes through portals
+use crate::circle_collider::{self, CircleCollider};
+use crate::levels::WINDOW_WIDTH;
+use crate::platform_controller::PlatformController;
+use crate::rect_collider::RectCollider;
+use crate::slope_collider::SlopeCollider;
+
+// the ball is as wide as a block
+pub const RADIUS: f32 = 16.0;
+// and a lot lighter than one
+pub const MASS: f32 = 10.0;
+const GRAVITY: f32 = 1.0;
+const MAX_FALL_SPEED: f32 = 40.0;
+// how much of its speed the ball keeps when it bounces off something
+pub const RESTITUTION: f32 = 0.4;
+// bounces slower than this just stop, so the ball settles down and rolls instead of hopping along
+const MIN_BOUNCE: f32 = 2.0;
+// how much speed the ball loses each tick rolling along the ground
+const ROLLING_RESISTANCE: f32 = 0.02;
+
+pub struct BallController {
+    collider: CircleCollider,
+    velocity: (f32, f32),
+    // how far round the ball has turned, in degrees, for drawing it
+    rotation: f32,
+    on_ground: bool,
+    start_x: f32,
+    start_y: f32,
+    obstacles: Vec<RectCollider>,
+    slopes: Vec<SlopeCollider>,
+    // how far right the ball can roll, the width of the level
+    level_width: f32,
+}
+
+impl BallController {
+    // new: a ball with the top left of it at (x, y), the same way blocks are placed
+    pub fn new(_x: f32, _y: f32)
+        -> BallController
+    {
+        BallController {
+            collider: CircleCollider::new(_x + RADIUS, _y + RADIUS, RADIUS),
+            velocity: (0.0, 0.0),
+            rotation: 0.0,
+            on_ground: false,
+            start_x: _x,
+            start_y: _y,
+            obstacles: vec!(),
+            slopes: vec!(),
+            level_width: WINDOW_WIDTH as f32,
+        }
+    }
+
+    pub fn collider(&self) -> CircleCollider { self.collider }
+    pub fn bounding_rect(&self) -> RectCollider { self.collider.bounding_rect() }
+    pub fn velocity(&self) -> (f32, f32) { self.velocity }
+    pub fn rotation(&self) -> f32 { self.rotation }
+
+    pub fn set_velocity(&mut self, _velocity: (f32, f32)) { self.velocity = _velocity; }
+    pub fn set_level_width(&mut self, _level_width: u32) { self.level_width = _level_width as f32 }
+    // set_middle: puts the middle of the ball somewhere, like coming out of a portal
+    pub fn set_middle(&mut self, x: f32, y: f32) {
+        self.collider.set_x(x);
+        self.collider.set_y(y);
+    }
+    pub fn shift(&mut self, dx: f32, dy: f32) { self.collider.shift(dx, dy); }
+
+    pub fn add_collider(&mut self, wall: RectCollider) {
+        self.obstacles.push(wall);
+    }
+
+    pub fn add_slope(&mut self, slope: SlopeCollider) {
+        self.slopes.push(slope);
+    }
+
+    // respawn: back to where the ball started, not moving
+    pub fn respawn(&mut self) {
+        self.set_middle(self.start_x + RADIUS, self.start_y + RADIUS);
+        self.velocity = (0.0, 0.0);
+    }
+
+    pub fn update(&mut self, platforms: &[PlatformController]) {
+        // ride along with a platform we're sitting on
+        for p in platforms {
+            if p.carries(&self.bounding_rect()) {
+                self.collider.shift(p.moved().0, p.moved().1);
+            }
+        }
+        if self.velocity.1 < MAX_FALL_SPEED {
+            self.velocity.1 += GRAVITY;
+        }
+        let solids: Vec<RectCollider> = self.obstacles.iter().copied().chain(platforms.iter().map(|p| p.collider())).collect();
+        let slopes = self.slopes.clone();
+        // fast moves are split into steps no longer than the ball is round, so it can't skip through anything
+        let steps = (self.velocity.0.abs().max(self.velocity.1.abs()) / RADIUS).ceil().max(1.0);
+        self.on_ground = false;
+        for _ in 0..steps as i32 {
+            self.collider.shift(self.velocity.0 / steps, self.velocity.1 / steps);
+            for c in &solids {
+                if let Some((normal, depth)) = self.collider.rect_penetration(c) {
+                    self.bounce(normal, depth);
+                }
+            }
+            for s in &slopes {
+                if let Some((normal, depth)) = self.collider.slope_penetration(s) {
+                    self.bounce(normal, depth);
+                }
+            }
+        }
+        if self.on_ground {
+            self.velocity.0 = circle_collider::resist(self.velocity.0, ROLLING_RESISTANCE);
+        }
+        let x = self.collider.x().clamp(RADIUS, RADIUS.max(self.level_width - RADIUS));
+        self.collider.set_x(x);
+        // rolling without slipping, the ball turns as far as it goes
+        self.rotation = (self.rotation + self.velocity.0 / RADIUS * 57.29) % 360.0;
+    }
+
+    // bounce: pushes the ball back out of something and takes away the speed it was going into it with (some of which
+    // comes back as a bounce). the speed along the surface is kept, which is what rolls it down slopes
+    fn bounce(&mut self, normal: (f32, f32), depth: f32) {
+        self.collider.shift(normal.0 * depth, normal.1 * depth);
+        let into = self.velocity.0 * normal.0 + self.velocity.1 * normal.1;
+        if into < 0.0 {
+            let restitution = if -into * RESTITUTION < MIN_BOUNCE { 0.0 } else { RESTITUTION };
+            self.velocity.0 -= (1.0 + restitution) * into * normal.0;
+            self.velocity.1 -= (1.0 + restitution) * into * normal.1;
+        }
+        // anything pushing up from underneath is ground
+        if normal.1 < -0.5 {
+            self.on_ground = true;
+        }
+    }
+}
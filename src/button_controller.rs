@@ -0,0 +1,43 @@
+use crate::rect_collider::RectCollider;
+
+// ButtonController: a button on the wall that the player presses with the interact key. it turns its wire on
+// for a while and then pops back out, or stays pressed for good if it has no time limit
+#[derive(Copy, Clone)]
+pub struct ButtonController {
+    collider: RectCollider,
+    wire: u32,
+    duration: u128,
+    pressed_at: Option<u128>
+}
+
+impl ButtonController {
+    pub fn new(_x: i32, _y: i32, _wire: u32, _duration: u32)
+        -> ButtonController
+    {
+        ButtonController {
+            collider: RectCollider::new(_x as f32, _y as f32, 64.0, 64.0),
+            wire: _wire,
+            duration: _duration as u128,
+            pressed_at: None
+        }
+    }
+    pub fn collider(&self) -> RectCollider { self.collider }
+    pub fn wire(&self) -> u32 { self.wire }
+
+    // press: pressing a button that's already on starts its countdown over. time is in milliseconds
+    pub fn press(&mut self, time: u128) {
+        self.pressed_at = Some(time);
+    }
+
+    pub fn is_on(&self, time: u128) -> bool {
+        self.pressed_at.is_some_and(|t| self.duration == 0 || time - t < self.duration)
+    }
+
+    // countdown: how much of its time the button has left, from 1 down to 0. None if it isn't counting down
+    pub fn countdown(&self, time: u128) -> Option<f32> {
+        match self.pressed_at {
+            Some(t) if self.duration > 0 && time - t < self.duration => Some(1.0 - (time - t) as f32 / self.duration as f32),
+            _ => None,
+        }
+    }
+}
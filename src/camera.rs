@@ -0,0 +1,77 @@
+use sdl2::rect::Rect;
+
+use crate::levels::{WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::rect_collider::RectCollider;
+
+// how far the player can move around the middle of the screen before the camera follows
+const DEAD_ZONE_WIDTH: f32 = 320.0;
+const DEAD_ZONE_HEIGHT: f32 = 240.0;
+
+// Camera: the part of the level that's on screen. everything in the level is positioned in level
+// coordinates, and the camera turns those into screen coordinates when it's drawn
+#[derive(Copy, Clone, Default)]
+pub struct Camera {
+    x: f32,
+    y: f32,
+    level_width: f32,
+    level_height: f32
+}
+
+impl Camera {
+    pub fn new(_level_width: u32, _level_height: u32)
+        -> Camera
+    {
+        Camera {
+            x: 0.0,
+            y: 0.0,
+            level_width: _level_width as f32,
+            level_height: _level_height as f32
+        }
+    }
+    pub fn position(&self) -> (f32, f32) { (self.x, self.y) }
+
+    // at: the same camera, looking from somewhere else (like partway between two ticks)
+    pub fn at(self, position: (f32, f32)) -> Camera {
+        Camera { x: position.0, y: position.1, ..self }
+    }
+
+    // follow: moves the camera just far enough to keep the target inside the dead zone,
+    // but never past the edges of the level
+    pub fn follow(&mut self, target: &RectCollider) {
+        let zone_left = self.x + (WINDOW_WIDTH as f32 - DEAD_ZONE_WIDTH)/2.0;
+        let zone_top = self.y + (WINDOW_HEIGHT as f32 - DEAD_ZONE_HEIGHT)/2.0;
+        if target.x() < zone_left {
+            self.x -= zone_left - target.x();
+        } else if target.x() + target.width() > zone_left + DEAD_ZONE_WIDTH {
+            self.x += target.x() + target.width() - zone_left - DEAD_ZONE_WIDTH;
+        }
+        if target.y() < zone_top {
+            self.y -= zone_top - target.y();
+        } else if target.y() + target.height() > zone_top + DEAD_ZONE_HEIGHT {
+            self.y += target.y() + target.height() - zone_top - DEAD_ZONE_HEIGHT;
+        }
+        self.clamp();
+    }
+
+    // pan: moves the camera by hand, for the editor
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        self.x += dx;
+        self.y += dy;
+        self.clamp();
+    }
+
+    fn clamp(&mut self) {
+        self.x = self.x.clamp(0.0, (self.level_width - WINDOW_WIDTH as f32).max(0.0));
+        self.y = self.y.clamp(0.0, (self.level_height - WINDOW_HEIGHT as f32).max(0.0));
+    }
+
+    // to_screen: where a rectangle in the level ends up on screen
+    pub fn to_screen(self, rect: Rect) -> Rect {
+        Rect::new(rect.x() - self.x.round() as i32, rect.y() - self.y.round() as i32, rect.width(), rect.height())
+    }
+
+    // to_world: where a point on screen (like the mouse) is in the level
+    pub fn to_world(self, x: i32, y: i32) -> (i32, i32) {
+        (x + self.x.round() as i32, y + self.y.round() as i32)
+    }
+}
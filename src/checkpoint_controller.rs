@@ -0,0 +1,32 @@
+use crate::rect_collider::RectCollider;
+
+// CheckpointController: a flag partway through a level. once the player touches it, they respawn there
+// instead of at the start, and if it saves blocks, the blocks respawn wherever they were when it was reached
+#[derive(Copy, Clone)]
+pub struct CheckpointController {
+    collider: RectCollider,
+    save_blocks: bool,
+    reached: bool
+}
+
+impl CheckpointController {
+    pub fn new(_x: i32, _y: i32, _save_blocks: bool)
+        -> CheckpointController
+    {
+        CheckpointController {
+            collider: RectCollider::new(_x as f32, _y as f32, 64.0, 128.0),
+            save_blocks: _save_blocks,
+            reached: false
+        }
+    }
+    pub fn collider(&self) -> RectCollider { self.collider }
+    pub fn save_blocks(&self) -> bool { self.save_blocks }
+    pub fn reached(&self) -> bool { self.reached }
+
+    pub fn reach(&mut self) { self.reached = true; }
+
+    // respawn_point: where the player comes back, standing at the foot of the flag
+    pub fn respawn_point(&self) -> (f32, f32) {
+        (self.collider.x(), self.collider.y() + self.collider.height() - 98.0)
+    }
+}
@@ -0,0 +1,164 @@
+use crate::rect_collider::RectCollider;
+use crate::slope_collider::SlopeCollider;
+
+// CircleCollider: a round collider, for things that roll. (x, y) is the middle of it
+#[derive(Copy, Clone)]
+pub struct CircleCollider {
+	x: f32,
+	y: f32,
+	r: f32,
+}
+
+impl CircleCollider {
+	pub fn new(x: f32, y: f32, r: f32) -> CircleCollider{
+		CircleCollider {
+			x,
+			y,
+			r,
+		}
+	}
+
+	// Getters
+	pub fn x(&self) -> f32 { self.x }
+	pub fn y(&self) -> f32 { self.y }
+	pub fn r(&self) -> f32 { self.r }
+
+	// Setters
+	pub fn set_x(&mut self, x: f32) { self.x = x; }
+	pub fn set_y(&mut self, y: f32) { self.y = y; }
+	pub fn shift(&mut self, dx: f32, dy: f32) {
+		self.x += dx;
+		self.y += dy;
+	}
+
+	pub fn left(&self) -> f32 { self.x - self.r }
+	pub fn right(&self) -> f32 { self.x + self.r }
+	pub fn top(&self) -> f32 { self.y - self.r }
+	pub fn bottom(&self) -> f32 { self.y + self.r }
+
+	// bounding_rect: the square the circle fits in, for the things that only know about rectangles
+	pub fn bounding_rect(&self) -> RectCollider {
+		RectCollider::new(self.left(), self.top(), self.right() - self.left(), self.bottom() - self.top())
+	}
+
+	// The penetration functions give back the way to push the circle to get it out of something, and how far
+
+	// rect_penetration: out of a rectangle, away from the closest point on it
+	pub fn rect_penetration(&self, rect: &RectCollider) -> Option<((f32, f32), f32)> {
+		let closest_x = self.x.clamp(rect.x(), rect.x() + rect.width());
+		let closest_y = self.y.clamp(rect.y(), rect.y() + rect.height());
+		let (dx, dy) = (self.x - closest_x, self.y - closest_y);
+		let distance = (dx.powf(2.0) + dy.powf(2.0)).powf(0.5);
+		if distance >= self.r {
+			None
+		}
+		else if distance > 0.0 {
+			Some(((dx / distance, dy / distance), self.r - distance))
+		}
+		else {
+			// the middle is inside the rectangle, so go out whichever side is closest
+			let (px, py) = self.bounding_rect().penetration(rect)?;
+			let depth = px.abs().max(py.abs());
+			Some(((px / depth, py / depth), depth))
+		}
+	}
+
+	// slope_penetration: out of a slope, square to its surface
+	pub fn slope_penetration(&self, slope: &SlopeCollider) -> Option<((f32, f32), f32)> {
+		let surface = slope.surface_y(self.x)?;
+		let bounds = slope.bounding_rect();
+		// under the slope the bottom is just a wall, and that's the level's job
+		if self.y > bounds.y() + bounds.height() { return None; }
+		let length = (1.0 + slope.steepness().powf(2.0)).powf(0.5);
+		let distance = (surface - self.y) / length;
+		if distance >= self.r || distance <= -self.r {
+			None
+		}
+		else {
+			Some(((slope.downhill() * slope.steepness() / length, -1.0 / length), self.r - distance))
+		}
+	}
+
+	// penetration: out of another circle, straight away from its middle
+	pub fn penetration(&self, other: &CircleCollider) -> Option<((f32, f32), f32)> {
+		if !check_collision(self, other) { return None; }
+		let (dx, dy) = (self.x - other.x(), self.y - other.y());
+		let distance = (dx.powf(2.0) + dy.powf(2.0)).powf(0.5);
+		if distance > 0.0 {
+			Some(((dx / distance, dy / distance), self.r + other.r() - distance))
+		}
+		else {
+			Some(((0.0, -1.0), self.r + other.r()))
+		}
+	}
+}
+
+pub fn check_collision(a: &CircleCollider, b: &CircleCollider) -> bool {
+	let radsum = a.r() + b.r();
+	let distsq = (a.x() - b.x()).powf(2.0) + (a.y() - b.y()).powf(2.0);
+	distsq < radsum.powf(2.0)
+}
+
+// resist: takes deltav off a speed, but never so much that it turns round
+pub fn resist(vel: f32, deltav: f32) -> f32 {
+	if vel > 0.0 {
+		(vel - deltav).max(0.0)
+	}
+	else if vel < 0.0 {
+		(vel + deltav).min(0.0)
+	}
+	else {
+		vel
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn close(a: ((f32, f32), f32), b: ((f32, f32), f32)) -> bool {
+		(a.0.0 - b.0.0).abs() < 0.001 && (a.0.1 - b.0.1).abs() < 0.001 && (a.1 - b.1).abs() < 0.001
+	}
+
+	#[test]
+	fn resting_on_a_rect_pushes_up() {
+		let floor = RectCollider::new(0.0, 100.0, 200.0, 50.0);
+		assert!(close(CircleCollider::new(50.0, 85.0, 16.0).rect_penetration(&floor).unwrap(), ((0.0, -1.0), 1.0)));
+		assert!(CircleCollider::new(50.0, 84.0, 16.0).rect_penetration(&floor).is_none());
+	}
+
+	#[test]
+	fn middle_inside_a_rect_goes_out_the_closest_side() {
+		let block = RectCollider::new(0.0, 100.0, 200.0, 50.0);
+		assert!(close(CircleCollider::new(5.0, 120.0, 16.0).rect_penetration(&block).unwrap(), ((-1.0, 0.0), 21.0)));
+		assert!(close(CircleCollider::new(100.0, 105.0, 16.0).rect_penetration(&block).unwrap(), ((0.0, -1.0), 21.0)));
+	}
+
+	#[test]
+	fn resting_on_a_slope_pushes_square_to_it() {
+		// the surface is at y = 200 - x, 45 degrees
+		let slope = SlopeCollider::new(0.0, 100.0, 100.0, 100.0, true);
+		let side = 0.5f32.powf(0.5);
+		let depth = 16.0 - 10.0 * side;
+		assert!(close(CircleCollider::new(50.0, 140.0, 16.0).slope_penetration(&slope).unwrap(), ((-side, -side), depth)));
+		assert!(CircleCollider::new(50.0, 100.0, 16.0).slope_penetration(&slope).is_none());
+	}
+
+	#[test]
+	fn slopes_let_go_of_balls_a_radius_under_them() {
+		let slope = SlopeCollider::new(0.0, 100.0, 100.0, 100.0, true);
+		let under = 16.0 / 0.5f32.powf(0.5);
+		assert!(CircleCollider::new(50.0, 150.0 + under - 1.0, 16.0).slope_penetration(&slope).is_some());
+		assert!(CircleCollider::new(50.0, 150.0 + under + 1.0, 16.0).slope_penetration(&slope).is_none());
+		// and anything under the slope's box is the level's problem
+		assert!(CircleCollider::new(50.0, 201.0, 16.0).slope_penetration(&slope).is_none());
+	}
+
+	#[test]
+	fn balls_push_each_other_apart() {
+		let other = CircleCollider::new(30.0, 0.0, 16.0);
+		assert!(close(CircleCollider::new(0.0, 0.0, 16.0).penetration(&other).unwrap(), ((-1.0, 0.0), 2.0)));
+		assert!(close(CircleCollider::new(30.0, 0.0, 16.0).penetration(&other).unwrap(), ((0.0, -1.0), 32.0)));
+		assert!(CircleCollider::new(-2.0, 0.0, 16.0).penetration(&other).is_none());
+	}
+}
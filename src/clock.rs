@@ -0,0 +1,71 @@
+use std::time::Duration;
+
+// the game steps forward 60 times a second, however often it's drawn
+pub const TICK_RATE: u64 = 60;
+pub const TICK_TIME: Duration = Duration::from_nanos(1_000_000_000 / TICK_RATE);
+
+// Clock: the game's own time. it only moves when the game ticks, never with the computer's clock, so the same
+// inputs always play out the same way (which is what replays and tests need)
+#[derive(Copy, Clone, Default)]
+pub struct Clock {
+    ticks: u64
+}
+
+impl Clock {
+    pub fn tick(&mut self) { self.ticks += 1; }
+
+    // millis: how long the game has been running, in milliseconds of game time
+    pub fn millis(&self) -> u128 {
+        self.ticks as u128 * 1000 / TICK_RATE as u128
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starts_at_zero() {
+        let clock = Clock::default();
+        assert_eq!(clock.ticks, 0);
+        assert_eq!(clock.millis(), 0);
+    }
+
+    #[test]
+    fn counts_ticks() {
+        let mut clock = Clock::default();
+        for _ in 0..10 {
+            clock.tick();
+        }
+        assert_eq!(clock.ticks, 10);
+    }
+
+    #[test]
+    fn millis_round_down_to_the_tick() {
+        let mut clock = Clock::default();
+        let mut millis = vec!();
+        for _ in 0..7 {
+            clock.tick();
+            millis.push(clock.millis());
+        }
+        assert_eq!(millis, vec!(16, 33, 50, 66, 83, 100, 116));
+    }
+
+    #[test]
+    fn a_second_is_a_tick_rate_of_ticks() {
+        let mut clock = Clock::default();
+        for _ in 0..TICK_RATE {
+            clock.tick();
+        }
+        assert_eq!(clock.millis(), 1000);
+        // a tick is a whole number of nanoseconds, so in real time that's a hair short of a second
+        assert_eq!(TICK_TIME * TICK_RATE as u32, Duration::from_nanos(999_999_960));
+    }
+
+    #[test]
+    fn millis_dont_overflow_on_long_games() {
+        // a year of ticks
+        let clock = Clock { ticks: TICK_RATE * 60 * 60 * 24 * 365 };
+        assert_eq!(clock.millis(), 1000 * 60 * 60 * 24 * 365);
+    }
+}
@@ -9,18 +9,20 @@ use sdl2::render::WindowCanvas;
 use sdl2::rect::Rect;
 use sdl2::pixels::Color;
 
+use crate::resources;
+
 pub(crate) fn show_credits(mut wincan: WindowCanvas, mut event_pump: sdl2::EventPump) {
     let texture_creator = wincan.texture_creator();
-    let andrew = texture_creator.load_texture("assets/out_of_game/credits/andrew_credits.png").unwrap();
-    let kira = texture_creator.load_texture("assets/out_of_game/credits/kira_credits.jpg").unwrap();
-    let josh = texture_creator.load_texture("assets/out_of_game/credits/josh_credits.png").unwrap();
-    let alvyn = texture_creator.load_texture("assets/out_of_game/credits/alvyn_credits.png").unwrap();
-    let greg = texture_creator.load_texture("assets/out_of_game/credits/greg_credits.jpg").unwrap();
-    let jake = texture_creator.load_texture("assets/out_of_game/credits/jake_credits.jpeg").unwrap();
-    let bryce = texture_creator.load_texture("assets/out_of_game/credits/bryce_credits.png").unwrap();
-    let austin = texture_creator.load_texture("assets/out_of_game/credits/austin_credits.png").unwrap();
-    let evan = texture_creator.load_texture("assets/out_of_game/credits/evan_credits.png").unwrap();
-    let loading_screen = texture_creator.load_texture("assets/out_of_game/loading_screen/stone_brick_loading_sprite_sheet_192x256.png").unwrap();
+    let andrew = texture_creator.load_texture(resources::path("assets/out_of_game/credits/andrew_credits.png")).unwrap();
+    let kira = texture_creator.load_texture(resources::path("assets/out_of_game/credits/kira_credits.jpg")).unwrap();
+    let josh = texture_creator.load_texture(resources::path("assets/out_of_game/credits/josh_credits.png")).unwrap();
+    let alvyn = texture_creator.load_texture(resources::path("assets/out_of_game/credits/alvyn_credits.png")).unwrap();
+    let greg = texture_creator.load_texture(resources::path("assets/out_of_game/credits/greg_credits.jpg")).unwrap();
+    let jake = texture_creator.load_texture(resources::path("assets/out_of_game/credits/jake_credits.jpeg")).unwrap();
+    let bryce = texture_creator.load_texture(resources::path("assets/out_of_game/credits/bryce_credits.png")).unwrap();
+    let austin = texture_creator.load_texture(resources::path("assets/out_of_game/credits/austin_credits.png")).unwrap();
+    let evan = texture_creator.load_texture(resources::path("assets/out_of_game/credits/evan_credits.png")).unwrap();
+    let loading_screen = texture_creator.load_texture(resources::path("assets/out_of_game/loading_screen/stone_brick_loading_sprite_sheet_192x256.png")).unwrap();
     let mut credits = Vec::new();
     credits.push(andrew);
     credits.push(kira);
@@ -0,0 +1,505 @@
+// Editor: lets you build a level with the mouse instead of guessing pixel coordinates
+use std::thread;
+use std::time::Duration;
+
+use sdl2::event::Event;
+use sdl2::image::LoadTexture;
+use sdl2::keyboard::{Keycode, Mod};
+use sdl2::mouse::{MouseButton, MouseUtil};
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, WindowCanvas};
+
+use crate::game;
+use crate::levels::{self, Door, Hazard, HazardKind, Level, LevelError, LevelObject, LevelPack, PackEntry, Platform, Surface, ALWAYS_ON, DOOR_HEIGHT, DOOR_WIDTH, TILE_SIZE};
+use crate::ball_controller::RADIUS;
+use crate::button_controller::ButtonController;
+use crate::camera::Camera;
+use crate::gate_controller::GateController;
+use crate::plate_controller::PlateController;
+use crate::resources;
+
+// the level the menu opens the editor on
+pub const DEFAULT_LEVEL: &str = "custom_level.txt";
+
+const GRID: i32 = TILE_SIZE as i32;
+// how fast new platforms move, in pixels per tick
+const PLATFORM_SPEED: u32 = 3;
+
+#[derive(Copy, Clone, PartialEq)]
+enum Tool {
+    PortalBlock,
+    NonPortalBlock,
+    PortalGlass,
+    Grid,
+    Start,
+    Checkpoint,
+    Block,
+    Ball,
+    GatePlate,
+    Door,
+    Slope,
+    Hazard(HazardKind),
+    Platform { portal: bool },
+}
+
+impl Tool {
+    fn name(&self) -> &'static str {
+        match self {
+            Tool::PortalBlock => "portal block",
+            Tool::NonPortalBlock => "non-portal block",
+            Tool::PortalGlass => "portal glass",
+            Tool::Grid => "grid",
+            Tool::Start => "start",
+            Tool::Checkpoint => "checkpoint (saves blocks)",
+            Tool::Block => "block",
+            Tool::Ball => "ball",
+            Tool::GatePlate => "gate/plate",
+            Tool::Door => "door",
+            Tool::Slope => "slope",
+            Tool::Hazard(kind) => kind.name(),
+            Tool::Platform { portal: true } => "portal platform",
+            Tool::Platform { portal: false } => "non-portal platform",
+        }
+    }
+
+    // the surface tools are dragged out, everything else is placed with a click
+    fn is_surface(&self) -> bool {
+        matches!(self, Tool::PortalBlock | Tool::NonPortalBlock | Tool::PortalGlass | Tool::Grid | Tool::Slope | Tool::Hazard(_) | Tool::Platform { .. })
+    }
+}
+
+/*
+Controls:
+    1-8: pick a tool (portal block, non-portal block, portal glass, start, block, gate/plate, door, slope)
+        (press 3 again to switch between portal glass and grids, 4 again for checkpoints and 5 again for balls)
+    9: pick the hazard tool, press again to switch between spikes, lava and acid
+    0: pick the moving platform tool, press again to switch between portal and non-portal platforms
+    left click: place an object, or drag to draw a surface
+        (slope: drag from the bottom of the slope to the top)
+        (gate/plate: click to place the plate, then drag out the gate)
+        (platform: drag out the platform, click each waypoint on its path and press enter)
+    right click / delete: remove the object under the mouse
+    arrow keys: resize the surface under the mouse
+    W/A/S/D: scroll around levels bigger than the window
+    ctrl+s: save, F5: save and play-test, escape: quit (press it twice to quit without saving your changes)
+ */
+pub(crate) fn run(mut wincan: WindowCanvas, mut event_pump: sdl2::EventPump, mouse: MouseUtil, filename: &str) {
+    let texture_creator = wincan.texture_creator();
+    let castle_bg = texture_creator.load_texture(resources::path("assets/in_game/level/background/castle/castle-bg.png")).unwrap();
+    let nonportal_surface = texture_creator.load_texture(resources::path("assets/in_game/level/brick/nonportal/stone_brick_64x64.png")).unwrap();
+    let portal_surface = texture_creator.load_texture(resources::path("assets/in_game/level/brick/portal/portal_brick_64x64.png")).unwrap();
+    let slope_texture = texture_creator.load_texture(resources::path("assets/in_game/level/brick/slope/slope_brick_64x64.png")).unwrap();
+    let spikes_texture = texture_creator.load_texture(resources::path("assets/in_game/level/hazard/spikes.png")).unwrap();
+    let lava_texture = texture_creator.load_texture(resources::path("assets/in_game/level/hazard/lava.png")).unwrap();
+    let acid_texture = texture_creator.load_texture(resources::path("assets/in_game/level/hazard/acid.png")).unwrap();
+    let portal_glass = texture_creator.load_texture(resources::path("assets/in_game/level/brick/portal_glass.png")).unwrap();
+    let block_texture = texture_creator.load_texture(resources::path("assets/in_game/block/block.png")).unwrap();
+    let pressure_plate = texture_creator.load_texture(resources::path("assets/in_game/level/pressure_plate/pressure_plate_spritesheet.png")).unwrap();
+    let gate = texture_creator.load_texture(resources::path("assets/in_game/level/gate/gate.png")).unwrap();
+    let button_texture = texture_creator.load_texture(resources::path("assets/in_game/level/button/button.png")).unwrap();
+    let checkpoint_texture = texture_creator.load_texture(resources::path("assets/in_game/level/checkpoint/checkpoint.png")).unwrap();
+    let light_texture = texture_creator.load_texture(resources::path("assets/in_game/level/light/light.png")).unwrap();
+    let door_sheet = texture_creator.load_texture(resources::path("assets/in_game/level/door/doors_sprite_sheet.png")).unwrap();
+    let p1sprite = texture_creator.load_texture(resources::path("assets/in_game/player/character/characters-sprites_condensed.png")).unwrap();
+
+    let level = match levels::parse_level(filename) {
+        Ok(level) => level,
+        // no file yet, so we're making a new level
+        Err(LevelError::Unreadable { .. }) => Level { objects: vec!() },
+        Err(e) => {
+            game::show_level_error(&wincan, &e);
+            return;
+        }
+    };
+    let level_size = level.size();
+    let mut camera = Camera::new(level_size.0, level_size.1);
+    let mut objects: Vec<LevelObject> = level.objects;
+
+    let mut tool = Tool::PortalBlock;
+    let mut drag_start: Option<(i32, i32)> = None;
+    let mut pending_plate: Option<(i32, i32)> = None;
+    let mut pending_platform: Option<Platform> = None;
+    let mut unsaved = false;
+    // escape was pressed with unsaved changes, and pressing it again quits anyway
+    let mut quitting = false;
+    let mut status = String::from("0-9 pick a tool, F5 to play-test");
+    let mut last_title = String::new();
+
+    mouse.show_cursor(true);
+    wincan.set_blend_mode(BlendMode::Blend);
+
+    'editor_loop: loop {
+        let mouse_state = event_pump.mouse_state();
+        let (mouse_x, mouse_y) = camera.to_world(mouse_state.x(), mouse_state.y());
+        for event in event_pump.poll_iter() {
+            match event {
+                Event::Quit { .. } | Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
+                    if !unsaved || quitting {
+                        break 'editor_loop;
+                    }
+                    quitting = true;
+                    status = String::from("unsaved changes, press escape again to throw them away or ctrl+s to save");
+                }
+                Event::KeyDown { keycode: Some(k), keymod, .. } => {
+                    quitting = false;
+                    match k {
+                        Keycode::Num1 | Keycode::Num2 | Keycode::Num3 | Keycode::Num4 | Keycode::Num5 | Keycode::Num6 | Keycode::Num7 | Keycode::Num8 => {
+                            tool = match k {
+                                Keycode::Num1 => Tool::PortalBlock,
+                                Keycode::Num2 => Tool::NonPortalBlock,
+                                // portal glass and grids share a key
+                                Keycode::Num3 if tool == Tool::PortalGlass => Tool::Grid,
+                                Keycode::Num3 => Tool::PortalGlass,
+                                Keycode::Num4 if tool == Tool::Start => Tool::Checkpoint,
+                                Keycode::Num4 => Tool::Start,
+                                Keycode::Num5 if tool == Tool::Block => Tool::Ball,
+                                Keycode::Num5 => Tool::Block,
+                                Keycode::Num6 => Tool::GatePlate,
+                                Keycode::Num7 => Tool::Door,
+                                _ => Tool::Slope,
+                            };
+                            pending_plate = None;
+                            pending_platform = None;
+                            drag_start = None;
+                            status = String::new();
+                        }
+                        Keycode::Num9 => {
+                            let next = match tool {
+                                Tool::Hazard(kind) => HazardKind::ALL[(HazardKind::ALL.iter().position(|k| *k == kind).unwrap() + 1) % HazardKind::ALL.len()],
+                                _ => HazardKind::Spikes,
+                            };
+                            tool = Tool::Hazard(next);
+                            pending_plate = None;
+                            pending_platform = None;
+                            drag_start = None;
+                            status = String::new();
+                        }
+                        Keycode::Num0 => {
+                            tool = Tool::Platform { portal: tool != Tool::Platform { portal: true } };
+                            pending_plate = None;
+                            pending_platform = None;
+                            drag_start = None;
+                            status = String::new();
+                        }
+                        Keycode::Return | Keycode::KpEnter => {
+                            if let Some(platform) = pending_platform.take() {
+                                if platform.waypoints.is_empty() {
+                                    status = String::from("a platform needs somewhere to move to, click its waypoints first");
+                                    pending_platform = Some(platform);
+                                    continue;
+                                }
+                                objects.push(LevelObject::Platform(platform));
+                                status = String::new();
+                                unsaved = true;
+                            }
+                        }
+                        Keycode::S if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) => {
+                            status = save(filename, &objects);
+                            unsaved = false;
+                        }
+                        Keycode::F5 => {
+                            if !objects.iter().any(|o| matches!(o, LevelObject::Start { .. })) {
+                                status = String::from("place a start point (4) before play-testing");
+                                continue;
+                            }
+                            status = save(filename, &objects);
+                            unsaved = false;
+                            let pack = LevelPack {
+                                name: String::from("playtest"),
+                                levels: vec!(PackEntry { file: filename.to_string(), display_name: format!("Play-testing {}", filename) }),
+                            };
+                            if let Err(e) = game::play(&mut wincan, &mut event_pump, &mouse, &None, &pack, false) {
+                                status = e;
+                            }
+                            // the game hides the cursor and changes the title, so take them back
+                            mouse.show_cursor(true);
+                            wincan.set_blend_mode(BlendMode::Blend);
+                            last_title = String::new();
+                            continue 'editor_loop;
+                        }
+                        Keycode::Delete | Keycode::Backspace => {
+                            unsaved |= remove_at(&mut objects, mouse_x, mouse_y);
+                        }
+                        Keycode::Left | Keycode::Right | Keycode::Up | Keycode::Down => {
+                            let (dw, dh) = match k {
+                                Keycode::Left => (-1, 0),
+                                Keycode::Right => (1, 0),
+                                Keycode::Up => (0, -1),
+                                _ => (0, 1),
+                            };
+                            unsaved |= resize_at(&mut objects, mouse_x, mouse_y, dw, dh);
+                        }
+                        Keycode::W | Keycode::A | Keycode::S | Keycode::D => {
+                            let (dx, dy) = match k {
+                                Keycode::W => (0, -GRID),
+                                Keycode::A => (-GRID, 0),
+                                Keycode::S => (0, GRID),
+                                _ => (GRID, 0),
+                            };
+                            camera.pan(dx as f32, dy as f32);
+                        }
+                        _ => {}
+                    }
+                }
+                Event::MouseButtonDown { mouse_btn: MouseButton::Left, x, y, .. } => {
+                    let (x, y) = camera.to_world(x, y);
+                    let cell = snap(x, y, level_size);
+                    if let Some(platform) = pending_platform.as_mut() {
+                        platform.waypoints.push(cell);
+                        status = format!("{} waypoint(s), click the next one or press enter when done", platform.waypoints.len());
+                    } else if tool.is_surface() || (tool == Tool::GatePlate && pending_plate.is_some()) {
+                        drag_start = Some(cell);
+                    } else {
+                        match tool {
+                            Tool::Start => {
+                                // a level only has one start
+                                objects.retain(|o| !matches!(o, LevelObject::Start { .. }));
+                                objects.push(LevelObject::Start { x: cell.0, y: cell.1 });
+                            }
+                            Tool::Checkpoint => objects.push(LevelObject::Checkpoint { x: cell.0, y: cell.1, save_blocks: true }),
+                            Tool::Block => objects.push(LevelObject::Block { x: cell.0, y: cell.1 }),
+                            Tool::Ball => objects.push(LevelObject::Ball { x: cell.0, y: cell.1 }),
+                            Tool::Door => objects.push(LevelObject::Door(Door { x: cell.0, y: cell.1, width: DOOR_WIDTH, height: DOOR_HEIGHT, wire: ALWAYS_ON })),
+                            _ => {
+                                pending_plate = Some(cell);
+                                status = String::from("now drag out the gate for this plate");
+                                continue;
+                            }
+                        }
+                        unsaved = true;
+                    }
+                }
+                Event::MouseButtonUp { mouse_btn: MouseButton::Left, x, y, .. } => {
+                    if let Some(start) = drag_start.take() {
+                        let (x, y) = camera.to_world(x, y);
+                        let end = snap(x, y, level_size);
+                        let (x, y, width, height) = drag_cells(start, end);
+                        let surface = Surface { x, y, width, height };
+                        match tool {
+                            Tool::PortalBlock => objects.push(LevelObject::PortalBlock(surface)),
+                            Tool::NonPortalBlock => objects.push(LevelObject::NonPortalBlock(surface)),
+                            Tool::PortalGlass => objects.push(LevelObject::PortalGlass(surface)),
+                            Tool::Grid => objects.push(LevelObject::Grid(surface)),
+                            // slopes go up the way they were dragged
+                            Tool::Slope => objects.push(LevelObject::Slope { surface, rises_right: (end.0 >= start.0) == (end.1 <= start.1) }),
+                            Tool::Hazard(kind) => objects.push(LevelObject::Hazard(Hazard { kind, surface })),
+                            // the platform isn't finished until it has a path
+                            Tool::Platform { portal } => {
+                                pending_platform = Some(Platform { surface, portal, speed: PLATFORM_SPEED, wire: ALWAYS_ON, waypoints: vec!() });
+                                status = String::from("now click each waypoint on the platform's path, then press enter");
+                                continue;
+                            }
+                            _ => {
+                                let (plate_x, plate_y) = pending_plate.take().unwrap();
+                                // the gate runs along whichever way it was dragged further
+                                let gate_vertical = height > width;
+                                let gate_length = (if gate_vertical { height } else { width } * TILE_SIZE) as i32;
+                                objects.push(LevelObject::GatePlate { plate_x, plate_y, gate_x: x, gate_y: y, gate_length, gate_vertical });
+                                status = String::new();
+                            }
+                        }
+                        unsaved = true;
+                    }
+                }
+                Event::MouseButtonDown { mouse_btn: MouseButton::Right, x, y, .. } => {
+                    let (x, y) = camera.to_world(x, y);
+                    unsaved |= remove_at(&mut objects, x, y);
+                }
+                _ => {}
+            }
+        }
+
+        /*
+        Render the level being edited
+         */
+        wincan.copy(&castle_bg, None, None).ok();
+        let mut plates: Vec<PlateController> = vec!();
+        let mut gates: Vec<GateController> = vec!();
+        for obj in objects.iter().chain(pending_platform.as_ref().map(|p| LevelObject::Platform(p.clone())).iter()) {
+            match *obj {
+                LevelObject::PortalBlock(surface) => game::draw_surface(&mut wincan, &camera, &portal_surface, &surface),
+                LevelObject::NonPortalBlock(surface) => game::draw_surface(&mut wincan, &camera, &nonportal_surface, &surface),
+                LevelObject::PortalGlass(surface) => game::draw_surface(&mut wincan, &camera, &portal_glass, &surface),
+                LevelObject::Grid(surface) => game::draw_grid(&mut wincan, &camera, &surface, 0),
+                LevelObject::Slope { surface, rises_right } => game::draw_slope(&mut wincan, &camera, &slope_texture, &surface, rises_right),
+                LevelObject::Hazard(hazard) => {
+                    let sprite = match hazard.kind {
+                        HazardKind::Spikes => &spikes_texture,
+                        HazardKind::Lava => &lava_texture,
+                        HazardKind::Acid => &acid_texture,
+                    };
+                    game::draw_surface(&mut wincan, &camera, sprite, &hazard.surface);
+                }
+                LevelObject::GatePlate { plate_x, plate_y, gate_x, gate_y, gate_length, gate_vertical } => {
+                    plates.push(PlateController::new(plate_x, plate_y, ALWAYS_ON, false));
+                    gates.push(GateController::new(gate_x, gate_y, gate_length, gate_vertical, ALWAYS_ON));
+                }
+                LevelObject::Plate { x, y, .. } => plates.push(PlateController::new(x, y, ALWAYS_ON, false)),
+                LevelObject::Button { x, y, ms, .. } => game::draw_button(&mut wincan, &camera, &button_texture, &ButtonController::new(x, y, ALWAYS_ON, ms), 0),
+                LevelObject::Gate { x, y, length, vertical, .. } => gates.push(GateController::new(x, y, length, vertical, ALWAYS_ON)),
+                LevelObject::Light { x, y, .. } => game::draw_light(&mut wincan, &camera, &light_texture, x, y, false),
+                LevelObject::Checkpoint { x, y, .. } => game::draw_checkpoint(&mut wincan, &camera, &checkpoint_texture, x, y, false),
+                LevelObject::Zone { x, y, width, height, .. } => {
+                    wincan.set_draw_color(Color::RGBA(0, 255, 255, 160));
+                    wincan.draw_rect(camera.to_screen(Rect::new(x, y, width, height))).ok();
+                }
+                // the size, rules, timers and logic live in the file, but not anywhere in the level
+                LevelObject::Size { .. } | LevelObject::Rules(_) | LevelObject::Timer { .. } | LevelObject::Logic { .. } => {}
+                LevelObject::Start { x, y } => {
+                    wincan.copy(&p1sprite, Rect::new(69, 0, 69, 98), camera.to_screen(Rect::new(x, y, 69, 98))).ok();
+                }
+                LevelObject::Block { x, y } => {
+                    wincan.copy(&block_texture, None, camera.to_screen(Rect::new(x, y, TILE_SIZE/2, TILE_SIZE/2))).ok();
+                }
+                LevelObject::Ball { x, y } => game::draw_ball(&mut wincan, &camera, (x as f32 + RADIUS, y as f32 + RADIUS), 0.0),
+                LevelObject::Door(door) => {
+                    wincan.copy(&door_sheet, Rect::new(0, 0, DOOR_WIDTH, DOOR_HEIGHT), camera.to_screen(Rect::new(door.x, door.y, door.width, door.height))).ok();
+                }
+                LevelObject::Platform(ref platform) => {
+                    game::draw_surface(&mut wincan, &camera, if platform.portal { &portal_surface } else { &nonportal_surface }, &platform.surface);
+                    draw_path(&mut wincan, &camera, platform);
+                }
+            }
+        }
+        game::draw_plate(&mut wincan, &camera, &pressure_plate, &plates);
+        game::draw_gate(&mut wincan, &camera, &gate, &gates);
+
+        // the grid everything snaps to, across the whole level
+        let area = camera.to_screen(Rect::new(0, 0, level_size.0, level_size.1));
+        wincan.set_draw_color(Color::RGBA(255, 255, 255, 40));
+        for x in (area.left()..area.right()).step_by(GRID as usize) {
+            wincan.draw_line((x, area.top()), (x, area.bottom())).ok();
+        }
+        for y in (area.top()..area.bottom()).step_by(GRID as usize) {
+            wincan.draw_line((area.left(), y), (area.right(), y)).ok();
+        }
+
+        // the plate waiting for its gate
+        if let Some((x, y)) = pending_plate {
+            wincan.copy(&pressure_plate, Rect::new(0, 0, 266, 266), camera.to_screen(Rect::new(x, y, TILE_SIZE, TILE_SIZE))).ok();
+        }
+
+        // what we're about to place
+        let cell = snap(mouse_x, mouse_y, level_size);
+        let preview = match drag_start {
+            Some(start) => {
+                let (x, y, width, height) = drag_cells(start, cell);
+                Rect::new(x, y, width * TILE_SIZE, height * TILE_SIZE)
+            }
+            None => Rect::new(cell.0, cell.1, TILE_SIZE, TILE_SIZE),
+        };
+        let preview = camera.to_screen(preview);
+        wincan.set_draw_color(Color::RGBA(255, 255, 0, 60));
+        wincan.fill_rect(preview).ok();
+        wincan.set_draw_color(Color::RGBA(255, 255, 0, 200));
+        wincan.draw_rect(preview).ok();
+
+        wincan.present();
+
+        // there's no text rendering in the game, so the editor's state lives in the title bar
+        let title = format!("Warp Wizards Editor - {}{} - tool: {}{}{}",
+                            filename, if unsaved { "*" } else { "" }, tool.name(),
+                            if status.is_empty() { "" } else { " - " }, status);
+        if title != last_title {
+            wincan.window_mut().set_title(&title).ok();
+            last_title = title;
+        }
+
+        thread::sleep(Duration::from_millis(16));
+    }
+}
+
+fn save(filename: &str, objects: &[LevelObject]) -> String {
+    match levels::save_level(filename, &Level { objects: objects.to_vec() }) {
+        Ok(_) => format!("saved to {}", resources::level_path(filename).display()),
+        Err(e) => e.to_string(),
+    }
+}
+
+// snap: the top left corner of the grid cell a point is in, staying inside the level
+fn snap(x: i32, y: i32, level_size: (u32, u32)) -> (i32, i32) {
+    (x.clamp(0, level_size.0 as i32 - 1) / GRID * GRID, y.clamp(0, level_size.1 as i32 - 1) / GRID * GRID)
+}
+
+// drag_cells: the tiles covered by dragging from one cell to another, as (x, y, width in tiles, height in tiles)
+fn drag_cells(start: (i32, i32), end: (i32, i32)) -> (i32, i32, u32, u32) {
+    let x = start.0.min(end.0);
+    let y = start.1.min(end.1);
+    let width = ((start.0.max(end.0) - x) / GRID + 1) as u32;
+    let height = ((start.1.max(end.1) - y) / GRID + 1) as u32;
+    (x, y, width, height)
+}
+
+// draw_path: the loop a moving platform goes round, drawn from the middle of the platform at each stop
+fn draw_path(wincan: &mut WindowCanvas, camera: &Camera, platform: &Platform) {
+    let s = platform.surface;
+    let (left, top) = camera.to_world(0, 0);
+    let middle = |(x, y): (i32, i32)| (x + (s.width * TILE_SIZE) as i32/2 - left, y + (s.height * TILE_SIZE) as i32/2 - top);
+    let stops: Vec<(i32, i32)> = std::iter::once((s.x, s.y)).chain(platform.waypoints.iter().copied()).map(middle).collect();
+    wincan.set_draw_color(Color::RGBA(255, 255, 255, 160));
+    for (i, stop) in stops.iter().enumerate() {
+        wincan.draw_line(*stop, stops[(i + 1) % stops.len()]).ok();
+        wincan.draw_rect(Rect::new(stop.0 - 4, stop.1 - 4, 8, 8)).ok();
+    }
+}
+
+// hit: is the point over this object?
+fn hit(obj: &LevelObject, x: i32, y: i32) -> bool {
+    let inside = |rx: i32, ry: i32, rw: u32, rh: u32| Rect::new(rx, ry, rw, rh).contains_point((x, y));
+    match *obj {
+        LevelObject::PortalBlock(s) | LevelObject::NonPortalBlock(s) | LevelObject::PortalGlass(s) | LevelObject::Grid(s)
+        | LevelObject::Hazard(Hazard { surface: s, .. }) | LevelObject::Platform(Platform { surface: s, .. }) =>
+            inside(s.x, s.y, s.width * TILE_SIZE, s.height * TILE_SIZE),
+        LevelObject::Slope { surface, rises_right } => surface.slope_collider(rises_right).contains_point(x as f32, y as f32),
+        LevelObject::GatePlate { plate_x, plate_y, gate_x, gate_y, gate_length, gate_vertical } => {
+            let length = gate_length.max(1) as u32;
+            inside(plate_x, plate_y, TILE_SIZE, TILE_SIZE)
+                || if gate_vertical { inside(gate_x, gate_y, TILE_SIZE, length) } else { inside(gate_x, gate_y, length, TILE_SIZE) }
+        }
+        LevelObject::Start { x: sx, y: sy } => inside(sx, sy, 69, 98),
+        LevelObject::Checkpoint { x: cx, y: cy, .. } => inside(cx, cy, TILE_SIZE, TILE_SIZE*2),
+        LevelObject::Block { x: bx, y: by } | LevelObject::Ball { x: bx, y: by } => inside(bx, by, TILE_SIZE/2, TILE_SIZE/2),
+        LevelObject::Door(d) => inside(d.x, d.y, d.width, d.height),
+        LevelObject::Plate { x: px, y: py, .. } | LevelObject::Button { x: px, y: py, .. } | LevelObject::Light { x: px, y: py, .. } =>
+            inside(px, py, TILE_SIZE, TILE_SIZE),
+        LevelObject::Gate { x: gx, y: gy, length, vertical, .. } => {
+            let length = length.max(1) as u32;
+            if vertical { inside(gx, gy, TILE_SIZE, length) } else { inside(gx, gy, length, TILE_SIZE) }
+        }
+        LevelObject::Zone { x: zx, y: zy, width, height, .. } => inside(zx, zy, width, height),
+        LevelObject::Size { .. } | LevelObject::Rules(_) | LevelObject::Timer { .. } | LevelObject::Logic { .. } => false,
+    }
+}
+
+// remove_at: deletes the topmost object under the point
+fn remove_at(objects: &mut Vec<LevelObject>, x: i32, y: i32) -> bool {
+    match objects.iter().rposition(|o| hit(o, x, y)) {
+        Some(i) => {
+            objects.remove(i);
+            true
+        }
+        None => false,
+    }
+}
+
+// resize_at: grows or shrinks the topmost surface under the point by whole tiles
+fn resize_at(objects: &mut [LevelObject], x: i32, y: i32, dw: i32, dh: i32) -> bool {
+    let resize = |s: &mut Surface| {
+        s.width = (s.width as i32 + dw).max(1) as u32;
+        s.height = (s.height as i32 + dh).max(1) as u32;
+    };
+    for obj in objects.iter_mut().rev() {
+        if !hit(obj, x, y) { continue; }
+        match obj {
+            LevelObject::PortalBlock(s) | LevelObject::NonPortalBlock(s) | LevelObject::PortalGlass(s) | LevelObject::Grid(s)
+            | LevelObject::Slope { surface: s, .. } | LevelObject::Hazard(Hazard { surface: s, .. })
+            | LevelObject::Platform(Platform { surface: s, .. }) => {
+                resize(s);
+                return true;
+            }
+            _ => return false,
+        }
+    }
+    false
+}
@@ -8,59 +8,94 @@ use std::net::UdpSocket;
 use sdl2::event::Event;
 use sdl2::image::LoadTexture;
 use sdl2::keyboard::Keycode;
+use sdl2::messagebox::{show_simple_message_box, MessageBoxFlag};
 use sdl2::mouse::MouseUtil;
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
-use sdl2::render::{Texture, WindowCanvas};
+use sdl2::render::{BlendMode, Texture, WindowCanvas};
 
 use crate::{levels, networking};
+use crate::levels::{Door, Hazard, HazardKind, Level, LevelError, LevelObject, LevelPack, LevelWatcher, PackEntry, Surface, DOOR_HEIGHT, DOOR_WIDTH, TILE_SIZE};
 use crate::animation_controller::Anim;
 use crate::animation_controller::AnimController;
 use crate::animation_controller::Condition;
 use crate::physics_controller::PhysicsController;
+use crate::physics_world;
 use crate::player::Player;
 use crate::portal_controller::{Portal, PortalController};
 use crate::rect_collider::RectCollider;
-use crate::object_controller::ObjectController;
+use crate::object_controller::{nearest_block, ObjectController};
+use crate::ball_controller::{BallController, RADIUS};
 use crate::plate_controller::PlateController;
+use crate::gate_controller::GateController;
+use crate::button_controller::ButtonController;
+use crate::camera::Camera;
+use crate::clock::{Clock, TICK_TIME};
+use crate::logic_controller::LogicController;
+use crate::platform_controller::PlatformController;
+use crate::checkpoint_controller::CheckpointController;
 use crate::credits;
+use crate::resources;
 use crate::networking::Multiplayer;
 use crate::remote_player::RemotePlayer;
 
-const TILE_SIZE: u32 = 64;
 // const BACKGROUND: Color = Color::RGBA(0, 128, 128, 255);
 
-const DOORW: u32 = 160;
-const DOORH: u32 = 230;
-//const DOOR_POS: (u32, u32) = (1060, 430);
-const FRAME_RATE: u64 = 60;
-const FRAME_TIME: Duration = Duration::from_millis(1000 / FRAME_RATE);
+// after a long hitch (like dragging the window) the game skips ahead instead of running hundreds of ticks to catch up
+const MAX_TICKS_PER_FRAME: u32 = 5;
+// frames aren't drawn any faster than this, so the game doesn't use a whole core when vsync is off
+const MAX_FRAME_RATE: u64 = 240;
+const MIN_FRAME_TIME: Duration = Duration::from_nanos(1_000_000_000 / MAX_FRAME_RATE);
+// anything that moves further than this in one tick jumped there (through a portal, or respawning), so it isn't
+// drawn sliding across the level on the way
+const SNAP_DISTANCE: f32 = 128.0;
 
 pub(crate) fn run(mut wincan: WindowCanvas, mut event_pump: sdl2::EventPump,
-                  mouse: MouseUtil, multiplayer: Option<Multiplayer>)
+                  mouse: MouseUtil, multiplayer: Option<Multiplayer>, pack_name: &str, watch: bool)
                   -> Result<(), String> {
+    let pack = match levels::load_pack(pack_name) {
+        Ok(pack) => pack,
+        Err(e) => return Err(show_level_error(&wincan, &e)),
+    };
+    play(&mut wincan, &mut event_pump, &mouse, &multiplayer, &pack, watch)?;
+    credits::show_credits(wincan, event_pump);
+    Ok(())
+}
+
+// play: plays through every level of a pack, returning once the last one is cleared or the player quits.
+// with watch on, the current level is reloaded whenever its file is saved
+pub(crate) fn play(wincan: &mut WindowCanvas, event_pump: &mut sdl2::EventPump,
+                   mouse: &MouseUtil, multiplayer: &Option<Multiplayer>, pack: &LevelPack, watch: bool)
+                   -> Result<(), String> {
     /*
     Renderer setup begins here.
     Currently only includes loading textures.
      */
     let texture_creator = wincan.texture_creator();
     // declare textures here
-    let bluewand = texture_creator.load_texture("assets/in_game/player/wand/blue/wand_sprite_blue.png").unwrap();
-    let orangewand = texture_creator.load_texture("assets/in_game/player/wand/orange/wand_sprite_orange.png").unwrap();
-    let cursor = texture_creator.load_texture("assets/in_game/cursor/cursor.png").unwrap();
-    let portalsprite = texture_creator.load_texture("assets/in_game/portal/portal-sprite-sheet.png").unwrap();
-    let p1sprite = texture_creator.load_texture("assets/in_game/player/character/characters-sprites_condensed.png").unwrap();
-    let door_sheet = texture_creator.load_texture("assets/in_game/level/door/doors_sprite_sheet.png").unwrap();
-    let castle_bg = texture_creator.load_texture("assets/in_game/level/background/castle/castle-bg.png").unwrap();
-    let nonportal_surface = texture_creator.load_texture("assets/in_game/level/brick/nonportal/stone_brick_64x64.png").unwrap();
-    let portal_surface = texture_creator.load_texture("assets/in_game/level/brick/portal/portal_brick_64x64.png").unwrap();
-    let portal_glass = texture_creator.load_texture("assets/in_game/level/brick/portal_glass.png").unwrap();
-    let block_texture = texture_creator.load_texture("assets/in_game/block/block.png").unwrap();
-    let pressure_plate = texture_creator.load_texture("assets/in_game/level/pressure_plate/pressure_plate_spritesheet.png").unwrap();
-    let gate = texture_creator.load_texture("assets/in_game/level/gate/gate.png").unwrap();
-    let loading_screen = texture_creator.load_texture("assets/out_of_game/loading_screen/stone_brick_loading_sprite_sheet_192x256.png").unwrap();
-    let potionsprite = texture_creator.load_texture("assets/in_game/player/potions/potions.png").unwrap();
-    let instructions = texture_creator.load_texture("assets/out_of_game/instructions/instructions.png").unwrap();
+    let bluewand = texture_creator.load_texture(resources::path("assets/in_game/player/wand/blue/wand_sprite_blue.png")).unwrap();
+    let orangewand = texture_creator.load_texture(resources::path("assets/in_game/player/wand/orange/wand_sprite_orange.png")).unwrap();
+    let cursor = texture_creator.load_texture(resources::path("assets/in_game/cursor/cursor.png")).unwrap();
+    let portalsprite = texture_creator.load_texture(resources::path("assets/in_game/portal/portal-sprite-sheet.png")).unwrap();
+    let p1sprite = texture_creator.load_texture(resources::path("assets/in_game/player/character/characters-sprites_condensed.png")).unwrap();
+    let door_sheet = texture_creator.load_texture(resources::path("assets/in_game/level/door/doors_sprite_sheet.png")).unwrap();
+    let castle_bg = texture_creator.load_texture(resources::path("assets/in_game/level/background/castle/castle-bg.png")).unwrap();
+    let nonportal_surface = texture_creator.load_texture(resources::path("assets/in_game/level/brick/nonportal/stone_brick_64x64.png")).unwrap();
+    let portal_surface = texture_creator.load_texture(resources::path("assets/in_game/level/brick/portal/portal_brick_64x64.png")).unwrap();
+    let slope_texture = texture_creator.load_texture(resources::path("assets/in_game/level/brick/slope/slope_brick_64x64.png")).unwrap();
+    let spikes_texture = texture_creator.load_texture(resources::path("assets/in_game/level/hazard/spikes.png")).unwrap();
+    let lava_texture = texture_creator.load_texture(resources::path("assets/in_game/level/hazard/lava.png")).unwrap();
+    let acid_texture = texture_creator.load_texture(resources::path("assets/in_game/level/hazard/acid.png")).unwrap();
+    let portal_glass = texture_creator.load_texture(resources::path("assets/in_game/level/brick/portal_glass.png")).unwrap();
+    let block_texture = texture_creator.load_texture(resources::path("assets/in_game/block/block.png")).unwrap();
+    let pressure_plate = texture_creator.load_texture(resources::path("assets/in_game/level/pressure_plate/pressure_plate_spritesheet.png")).unwrap();
+    let gate = texture_creator.load_texture(resources::path("assets/in_game/level/gate/gate.png")).unwrap();
+    let button_texture = texture_creator.load_texture(resources::path("assets/in_game/level/button/button.png")).unwrap();
+    let checkpoint_texture = texture_creator.load_texture(resources::path("assets/in_game/level/checkpoint/checkpoint.png")).unwrap();
+    let light_texture = texture_creator.load_texture(resources::path("assets/in_game/level/light/light.png")).unwrap();
+    let loading_screen = texture_creator.load_texture(resources::path("assets/out_of_game/loading_screen/stone_brick_loading_sprite_sheet_192x256.png")).unwrap();
+    let potionsprite = texture_creator.load_texture(resources::path("assets/in_game/player/potions/potions.png")).unwrap();
+    let instructions = texture_creator.load_texture(resources::path("assets/out_of_game/instructions/instructions.png")).unwrap();
 
     /*
     Setup loading screen
@@ -94,9 +129,7 @@ pub(crate) fn run(mut wincan: WindowCanvas, mut event_pump: sdl2::EventPump,
     Game state setup begins here.
      */
     // Colliders
-    let door_collider = RectCollider::new((1280 - DOORW + 25) as f32, (720 - DOORH + 25) as f32, (DOORW/2 - 10) as f32, (DOORH - 90) as f32);
     let p1collider = RectCollider::new(0.0, 0.0, 69.0, 98.0);
-    let block_collider = RectCollider::new(200.0, (720-(3*TILE_SIZE as i32)/2) as f32, (TILE_SIZE/2) as f32, (TILE_SIZE/2) as f32);
 
     // Controllers and portals
     let p1physcon = PhysicsController::new(75.0, 500.0, 8.0, 0.7, 20.0, 2, 0.2, 1.0, 40.0, vec!());
@@ -104,8 +137,8 @@ pub(crate) fn run(mut wincan: WindowCanvas, mut event_pump: sdl2::EventPump,
     let orange_portal = Portal::new(1);
     let p1portalcon = PortalController::new(-10, 60, 20, 65, p1physcon.clone(), vec!(blue_portal, orange_portal), vec!(), vec!(), vec!());
 
-    // dummy pressure plate controller, to be used later
-    let mut platecon = PlateController::new(0, 0, 0, 0, 0, false);
+    // everything in the current level besides the player
+    let mut contents = LevelContents::default();
     /*
     Animations
     the first parameter is the frames to use
@@ -123,11 +156,11 @@ pub(crate) fn run(mut wincan: WindowCanvas, mut event_pump: sdl2::EventPump,
 
     // Entities
     let mut player = Player::new(p1physcon, p1collider, p1anim, p1portalcon);
-    let mut block = ObjectController::new(block_collider);
 
     //level data
-    let mut current_level = 0; // what level are we on?
-    let final_level = 4; // what level is the last one?
+    let mut current_level = 0; // what level of the pack are we on?
+    let mut deaths = 0; // how many times the player has died on this level
+    let final_level = pack.levels.len() - 1; // what level is the last one?
 
     //which type of portal are we creating?
     // false = wand (raycast)
@@ -137,48 +170,16 @@ pub(crate) fn run(mut wincan: WindowCanvas, mut event_pump: sdl2::EventPump,
     let mut paused = true;
     let mut last_pause_time = SystemTime::now();
 
-    // data to help get the time elapsed and dash time
-    let mut first_loop = 0;
-    let mut start_time = 0;
-    let mut elapsed_time = 0;
+    // the game's own time, which only moves as it ticks, and dash time
+    let mut clock = Clock::default();
     let mut time_of_dash = 0; // the time that 'E' is pressed
     let mut currently_dashing = 0; // 0 is not dashing, 1 if dashing
     let mut first_press: i8 = 0;
 
-    let mut level = levels::parse_level("level0.txt");
+    let mut level = open_level(wincan, &pack.levels[current_level])?;
     // we read in the level from a file and add the necessary colliders and stuff
-    let mut level_has_gate = false;
-    for obj in level.iter() {
-        let new_collider = || {
-            RectCollider::new(obj[1].parse::<i32>().unwrap() as f32, obj[2].parse::<i32>().unwrap() as f32, (obj[3].parse::<u32>().unwrap() * TILE_SIZE) as f32, (obj[4].parse::<u32>().unwrap() * TILE_SIZE) as f32)
-        };
-        if obj[0] == "start" {
-            player.physics.set_start_x(obj[1].parse::<i32>().unwrap() as f32);
-            player.physics.set_start_y(obj[2].parse::<i32>().unwrap() as f32);
-            player.respawn();
-            block.set_start_pos(obj[3].parse::<i32>().unwrap() as f32, obj[4].parse::<i32>().unwrap() as f32);
-            block.respawn();
-        }
-        if obj[0] == "portalblock" {
-            player.add_collider(new_collider(), "portalblock");
-            block.add_collider(new_collider());
-        }
-        if obj[0] == "nonportalblock" {
-            player.add_collider(new_collider(), "nonportalblock");
-            block.add_collider(new_collider());
-        }
-        if obj[0] == "portalglass" {
-            player.add_collider(new_collider(), "portalglass");
-            block.add_collider(new_collider());
-        }
-        if obj[0] == "gateplate" {
-            platecon = PlateController::new(obj[1].parse::<i32>().unwrap(), obj[2].parse::<i32>().unwrap(), obj[3].parse::<i32>().unwrap(), obj[4].parse::<i32>().unwrap(), obj[5].parse::<i32>().unwrap(), obj[6].parse::<i32>().unwrap() == 1);
-            level_has_gate = true;
-        }
-    }
-    if !level_has_gate {
-        platecon = PlateController::new(0, 0, 0, 0, 0, false);
-    }
+    load_level(&level, &mut player, &mut contents);
+    let mut watcher = if watch { Some(LevelWatcher::new(&pack.levels[current_level].file)) } else { None };
 
     /*
     Networking setup
@@ -216,23 +217,18 @@ pub(crate) fn run(mut wincan: WindowCanvas, mut event_pump: sdl2::EventPump,
      */
     // ****************************************************************
     let mut level_cleared_time: Option<Instant> = None;
+    // how far the game is behind the clock, and where everything was before the last tick
+    let mut last_frame = Instant::now();
+    let mut lag = Duration::from_secs(0);
+    let mut previous = Snapshot::take(&player, &contents);
     /*
     Begin game update loop.
      */
     'game_loop: loop {
         // Timer tick
         let tick = Instant::now();
-        // get the elapsed time
-        match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
-            Ok(n) => {
-                if first_loop == 0 {
-                    start_time = n.as_millis();
-                    first_loop += 1;
-                }
-                elapsed_time = n.as_millis() - start_time;
-            }
-            Err(_) => panic!("SystemTime before UNIX EPOCH!"),
-        }
+        lag = (lag + (tick - last_frame)).min(TICK_TIME * MAX_TICKS_PER_FRAME);
+        last_frame = tick;
         /*
         Process local game input
          */
@@ -241,26 +237,31 @@ pub(crate) fn run(mut wincan: WindowCanvas, mut event_pump: sdl2::EventPump,
                 Event::Quit { .. } | Event::KeyDown { keycode: Some(Keycode::Escape), .. } => break 'game_loop,
                 Event::KeyDown { keycode: Some(Keycode::S), .. } =>
                 {
-                    if block.carried {
+                    if let Some(block) = contents.blocks.iter_mut().find(|b| b.carried) {
                         block.put_down();
-                    } else if player.collider.is_touching(&block.collider()) {
+                    } else if let Some(i) = nearest_block(&contents.blocks, &player) {
                         if remote_player.is_some() {
                             let block_data = remote_player.unwrap().block_data;
-                            let carried_by_remote_player = block_data.2;
+                            let carried_by_remote_player = block_data.2 && block_data.3 == i as i32;
                             if !carried_by_remote_player {
-                                block.picked_up(&player);
+                                contents.blocks[i].picked_up(&player);
                             }
                         } else {
-                            block.picked_up(&player);
+                            contents.blocks[i].picked_up(&player);
                         }
                     }
                 },
                 Event::KeyDown { keycode: Some(Keycode::R), .. } =>
                 {
-                    //restart level
+                    //restart level, from the last checkpoint reached
                     player.respawn();
                     player.portal.close_all();
-                    block.respawn();
+                    for block in contents.blocks.iter_mut() {
+                        block.respawn();
+                    }
+                    for ball in contents.balls.iter_mut() {
+                        ball.respawn();
+                    }
                 },
                 Event::KeyDown { keycode: Some(Keycode::P), .. } =>
                 {
@@ -273,6 +274,14 @@ pub(crate) fn run(mut wincan: WindowCanvas, mut event_pump: sdl2::EventPump,
                 Event::KeyDown { keycode: Some(Keycode::LShift), .. } => {
                     player.portal.close_all();
                 }
+                Event::KeyDown { keycode: Some(Keycode::F), .. } => {
+                    // press any button the player is standing at
+                    for button in contents.buttons.iter_mut() {
+                        if player.collider.is_touching(&button.collider()) {
+                            button.press(clock.millis());
+                        }
+                    }
+                }
                 Event::KeyDown { keycode: Some(Keycode::LAlt), .. } => {
                         throwing_portal = !throwing_portal;
                 }
@@ -286,7 +295,7 @@ pub(crate) fn run(mut wincan: WindowCanvas, mut event_pump: sdl2::EventPump,
             if multiplayer.is_some() {
                 // send
                 if send_socket.is_some() {
-                    let buf = networking::pack_data(&mut player, &block, &multiplayer);
+                    let buf = networking::pack_data(&mut player, &contents.blocks, multiplayer);
                     if let Err(e) = send_socket.as_ref().unwrap().send(&buf) {
                         eprintln!("Failed sending game data to other player: {}", e);
                     };
@@ -297,7 +306,7 @@ pub(crate) fn run(mut wincan: WindowCanvas, mut event_pump: sdl2::EventPump,
                     Ok(mut buf) =>{
                         let player_data = networking::unpack_player_data(&mut buf).unwrap();
                         let portal_data: (f32, f32, f32) = networking::unpack_portal_data(&mut buf);
-                        let block_data: (i32, i32, bool) = networking::unpack_block_data(&mut buf);
+                        let block_data: (i32, i32, bool, i32) = networking::unpack_block_data(&mut buf);
                         let wand_data: (i32, i32, f32) = networking::unpack_wand_data(&mut buf);
                         let potion_data: (f32, f32, f32, i32) = networking::unpack_potion_data(&mut buf);
                         remote_player = Some(RemotePlayer {player_data, portal_data, block_data, wand_data, potion_data });
@@ -314,87 +323,47 @@ pub(crate) fn run(mut wincan: WindowCanvas, mut event_pump: sdl2::EventPump,
                 }
             }
             if current_level == final_level { break 'game_loop; }
-            player.reset_colliders();
-            block.reset_colliders();
             current_level += 1;
-            let current_level_file = &format!("level{}.txt", current_level);
-            level = levels::parse_level(current_level_file);
+            level = open_level(wincan, &pack.levels[current_level])?;
             // we read in the level from a file and add the necessary colliders and stuff
-            let mut level_has_gate = false;
-            for obj in level.iter() {
-                let new_collider = || {
-                    RectCollider::new(obj[1].parse::<i32>().unwrap() as f32, obj[2].parse::<i32>().unwrap() as f32, (obj[3].parse::<u32>().unwrap() * TILE_SIZE) as f32, (obj[4].parse::<u32>().unwrap() * TILE_SIZE) as f32)
-                };
-                if obj[0] == "start" {
-                    player.physics.set_start_x(obj[1].parse::<i32>().unwrap() as f32);
-                    player.physics.set_start_y(obj[2].parse::<i32>().unwrap() as f32);
-                    player.respawn();
-                    block.set_start_pos(obj[3].parse::<i32>().unwrap() as f32, obj[4].parse::<i32>().unwrap() as f32);
-                    block.respawn();
-                }
-                if obj[0] == "portalblock" {
-                    player.add_collider(new_collider(), "portalblock");
-                    block.add_collider(new_collider());
-                }
-                if obj[0] == "nonportalblock" {
-                    player.add_collider(new_collider(), "nonportalblock");
-                    block.add_collider(new_collider());
-                }
-                if obj[0] == "portalglass" {
-                    player.add_collider(new_collider(), "portalglass");
-                    block.add_collider(new_collider());
-                }
-                if obj[0] == "gateplate" {
-                    platecon = PlateController::new(obj[1].parse::<i32>().unwrap(), obj[2].parse::<i32>().unwrap(), obj[3].parse::<i32>().unwrap(), obj[4].parse::<i32>().unwrap(), obj[5].parse::<i32>().unwrap(), obj[6].parse::<i32>().unwrap() == 1);
-                    level_has_gate = true;
-                }
-            }
-            if !level_has_gate {
-                platecon = PlateController::new(0, 0, 0, 0, 0, false);
-            }
+            load_level(&level, &mut player, &mut contents);
             player.unstop();
             level_cleared_time = None;
-        }
-
-        let keystate: HashSet<Keycode> = event_pump
-            .keyboard_state()
-            .pressed_scancodes()
-            .filter_map(Keycode::from_scancode)
-            .collect();
-
-        move_player(&mut player, &keystate);
-
-        // dash controller
-        if keystate.contains(&Keycode::E) && currently_dashing == 0 && (elapsed_time >= time_of_dash + (6*player.physics.dash_time())) {
-            time_of_dash = elapsed_time;
-            currently_dashing = 1;
-        }
-        if currently_dashing == 1 {
-            first_press += 1;
-            if elapsed_time >= time_of_dash + player.physics.dash_time() {
-                currently_dashing = 0;
-                first_press = 0;
-                player.physics.stop_dash();
+            deaths = 0;
+            if watch {
+                watcher = Some(LevelWatcher::new(&pack.levels[current_level].file));
             }
-            else {
-                player.physics.dash(player.physics.speed(), first_press);
+        }
+        /*
+        Reload the level if its file was saved
+         */
+        if watcher.as_mut().is_some_and(|w| w.changed()) {
+            match levels::parse_level(&pack.levels[current_level].file) {
+                Ok(reloaded) => {
+                    level = reloaded;
+                    load_level(&level, &mut player, &mut contents);
+                    // the rules might have changed
+                    set_level_title(wincan, &pack.levels[current_level], &level, deaths);
+                    // start over from a standstill, as if the level had just been entered
+                    player.respawn();
+                    player.physics.set_fall_speed(0.0);
+                    player.physics.stop_dash();
+                    currently_dashing = 0;
+                    first_press = 0;
+                    level_cleared_time = None;
+                }
+                // keep playing the old version until the file is fixed, a message box every save would get in the way
+                Err(e) => eprintln!("{}", e),
             }
         }
 
-        // Teleport the player
-        player.portal.teleport(&mut player.collider, &mut player.physics);
-
-        /*
-       Local Game Input Processed
-        */
-        // *************************************************************************
         /*
         Begin Networking
          */
         if multiplayer.is_some() {
             // send
             if send_socket.is_some() {
-                let buf = networking::pack_data(&mut player, &block, &multiplayer);
+                let buf = networking::pack_data(&mut player, &contents.blocks, multiplayer);
                 if let Err(e) = send_socket.as_ref().unwrap().send(&buf) {
                     eprintln!("Failed sending game data to other player: {}", e);
                 };
@@ -405,7 +374,7 @@ pub(crate) fn run(mut wincan: WindowCanvas, mut event_pump: sdl2::EventPump,
                 Ok(mut buf) =>{
                     let player_data = networking::unpack_player_data(&mut buf).unwrap();
                     let portal_data: (f32, f32, f32) = networking::unpack_portal_data(&mut buf);
-                    let block_data: (i32, i32, bool) = networking::unpack_block_data(&mut buf);
+                    let block_data: (i32, i32, bool, i32) = networking::unpack_block_data(&mut buf);
                     let wand_data: (i32, i32, f32) = networking::unpack_wand_data(&mut buf);
                     let potion_data: (f32, f32, f32, i32) = networking::unpack_potion_data(&mut buf);
                     remote_player = Some(RemotePlayer {player_data, portal_data, block_data, wand_data, potion_data });
@@ -424,105 +393,210 @@ pub(crate) fn run(mut wincan: WindowCanvas, mut event_pump: sdl2::EventPump,
         /*
         End Networking
          */
-        // *************************************************************************************
+
+        let keystate: HashSet<Keycode> = event_pump
+            .keyboard_state()
+            .pressed_scancodes()
+            .filter_map(Keycode::from_scancode)
+            .collect();
+
         /*
-        Begin Game State Update:
-        Check if level is cleared, kill condition, respawn condition, flip horizontal,
-        and update portals.
+        Step the game forward a tick at a time, as many ticks as it takes to catch up with the clock.
+        Everything that moves does it here, so the game plays at the same speed however fast it draws.
          */
-        let mut remote_player_collider = RectCollider::new(-300.0, -300.0, 69.0, 98.0);
+        while lag >= TICK_TIME {
+            previous = Snapshot::take(&player, &contents);
+            move_player(&mut player, &keystate, clock.millis());
 
-        // kill condition
-        if !player.is_dead() && (player.physics.x() < 0.0 || player.physics.x() > 1280.0 || player.physics.y() < 0.0 || player.physics.y() > 720.0) {
-            player.kill();
-        }
+            // dash controller
+            if keystate.contains(&Keycode::E) && player.physics.can_dash() && currently_dashing == 0 && player.physics.dash_ready(clock.millis(), time_of_dash) {
+                time_of_dash = clock.millis();
+                currently_dashing = 1;
+            }
+            if currently_dashing == 1 {
+                first_press += 1;
+                if player.physics.dash_over(clock.millis(), time_of_dash) {
+                    currently_dashing = 0;
+                    first_press = 0;
+                    player.physics.stop_dash();
+                }
+                else {
+                    player.physics.dash(player.physics.speed(), first_press);
+                }
+            }
 
-        // respawn condition
-        if player.is_dead() {
-            player.respawn();
-        }
+            // Teleport the player
+            player.portal.teleport(clock.millis(), &mut player.collider, &mut player.physics);
 
-        player.update(platecon);
-        block.update(&player, remote_player);
-        platecon.update_plate(block.collider());
+            /*
+           Local Game Input Processed
+            */
+            /*
+            Begin Game State Update:
+            Check if level is cleared, kill condition, respawn condition, flip horizontal,
+            and update portals.
+             */
+            // kill condition
+            let (level_width, level_height) = level.size();
+            let out_of_bounds = player.physics.x() < 0.0 || player.physics.x() > level_width as f32 || player.physics.y() < 0.0 || player.physics.y() > level_height as f32;
+            if !player.is_dead() && (out_of_bounds || touching_hazard(&player.collider, &contents.hazards)) {
+                player.kill();
+                // the blocks and balls go back to the start too, so dying with one can't lose it
+                for block in contents.blocks.iter_mut() {
+                    block.respawn();
+                }
+                for ball in contents.balls.iter_mut() {
+                    ball.respawn();
+                }
+                deaths += 1;
+                set_level_title(wincan, &pack.levels[current_level], &level, deaths);
+            }
 
-        // do we need to flip the player?
-        player.flip_horizontal =
-            if player.physics.speed() > 0.0 && player.flip_horizontal {
-                false
-            } else if player.physics.speed() < 0.0 && !player.flip_horizontal {
-                true
-            } else {
-                player.flip_horizontal
-            };
+            // respawn condition
+            if player.is_dead() {
+                player.respawn();
+            }
 
-        // create the portals
-        if remote_player.is_some() {
-            let network = multiplayer.as_ref().unwrap();
-            match network.mode {
-                networking::Mode::MultiplayerPlayer1 => {
-                    if event_pump.mouse_state().left() {
-                        if throwing_portal {
-                            player.portal.throw_potion(0, event_pump.mouse_state().x(), event_pump.mouse_state().y());
-                        } else {
-                            player.portal.open_portal(0);
-                        }
-                    }
-                    let remote_portal = remote_player.unwrap().portal_data;
-                    if remote_portal.0 != 0.0 && remote_portal.1 != 0.0 {
-                        player.portal.portals[1].open(remote_portal.0, remote_portal.1, remote_portal.2);
-                    }
-                },
-                networking::Mode::MultiplayerPlayer2 => {
-                    if event_pump.mouse_state().left() {
-                        if throwing_portal {
-                            player.portal.throw_potion(1, event_pump.mouse_state().x(), event_pump.mouse_state().y());
-                        } else {
-                            player.portal.open_portal(1);
+            // the platforms move before anything rides them, so riders go along with this tick's move
+            for platform in contents.platforms.iter_mut() {
+                platform.update(&contents.logic);
+            }
+            physics_world::step(clock.millis(), &mut player, &mut contents.blocks, &mut contents.balls, remote_player, &contents.gates, &contents.platforms);
+            contents.camera.follow(&player.collider);
+            // the mouse is on the screen, but the wand and potions aim at a spot in the level
+            let (mouse_x, mouse_y) = contents.camera.to_world(event_pump.mouse_state().x(), event_pump.mouse_state().y());
+            for block in contents.blocks.iter_mut() {
+                // a block dropped into a hazard (or off the bottom of the level) goes back to where it started
+                if !block.carried && (touching_hazard(&block.collider(), &contents.hazards) || block.y() > level_height as i32) {
+                    block.respawn();
+                }
+                // and so does one that's taken (or pushed) through a grid
+                if (block.carried && player.in_grid()) || player.grids().iter().any(|g| block.collider().is_touching(g)) {
+                    block.respawn();
+                }
+            }
+            // the first time the player reaches a checkpoint, that's where they come back to
+            for checkpoint in contents.checkpoints.iter_mut() {
+                if !checkpoint.reached() && player.collider.is_touching(&checkpoint.collider()) {
+                    checkpoint.reach();
+                    let (x, y) = checkpoint.respawn_point();
+                    player.physics.set_start_x(x);
+                    player.physics.set_start_y(y);
+                    if checkpoint.save_blocks() {
+                        // blocks that are on the move keep their old spot, they might not have anywhere to land
+                        for block in contents.blocks.iter_mut().filter(|b| !b.carried && !b.in_air()) {
+                            block.set_start_pos(block.collider().x(), block.collider().y());
                         }
                     }
-                    let remote_portal = remote_player.unwrap().portal_data;
-                    if remote_portal.0 != 0.0 && remote_portal.1 != 0.0 {
-                        player.portal.portals[0].open(remote_portal.0, remote_portal.1, remote_portal.2);
-                    }
                 }
             }
-        } else {
-            if event_pump.mouse_state().left() {
-                if throwing_portal {
-                    player.portal.throw_potion(0, event_pump.mouse_state().x(), event_pump.mouse_state().y());
-                } else {
-                    player.portal.open_portal(0);
+            // balls roll away from things like hazards and grids the same way blocks do, and press plates too
+            for ball in contents.balls.iter_mut() {
+                let collider = ball.bounding_rect();
+                if touching_hazard(&collider, &contents.hazards) || collider.y() > level_height as f32 || player.grids().iter().any(|g| collider.is_touching(g)) {
+                    ball.respawn();
                 }
             }
-            if event_pump.mouse_state().right() {
-                if throwing_portal {
-                    player.portal.throw_potion(1, event_pump.mouse_state().x(), event_pump.mouse_state().y());
+            let block_colliders: Vec<RectCollider> = contents.blocks.iter().map(|b| b.collider()).chain(contents.balls.iter().map(|b| b.bounding_rect())).collect();
+            let players = player_colliders(&player, remote_player);
+            for plate in contents.plates.iter_mut() {
+                plate.update_plate(&block_colliders, &players);
+            }
+            // with the plates pressed (or not), the wires can be worked out and everything on them switched
+            contents.logic.update(clock.millis(), &contents.plates, &contents.buttons, &players);
+            for gate in contents.gates.iter_mut() {
+                gate.set_open(contents.logic.is_on(gate.wire()));
+            }
+
+            // do we need to flip the player?
+            player.flip_horizontal =
+                if player.physics.speed() > 0.0 && player.flip_horizontal {
+                    false
+                } else if player.physics.speed() < 0.0 && !player.flip_horizontal {
+                    true
                 } else {
-                    player.portal.open_portal(1);
-                }
+                    player.flip_horizontal
+                };
+
+            // on levels with only one way to make portals, that's the one in the player's hand
+            if !player.portal.can_shoot() {
+                throwing_portal = true;
+            } else if !player.portal.can_throw() {
+                throwing_portal = false;
             }
-        }
-        /*
-        Level cleared Logic
-         */
-        if remote_player.is_some() {
-            let remote_player = remote_player.unwrap().player_data;
-            remote_player_collider = RectCollider::new(remote_player.0, remote_player.1, 69.0, 98.0);
-            if level_cleared_time.is_none() && player.collider.is_touching(&door_collider) && remote_player_collider.is_touching(&door_collider) {
-                level_cleared_time = Some(Instant::now());
-                player.stop();
+
+            // create the portals
+            if remote_player.is_some() {
+                let network = multiplayer.as_ref().unwrap();
+                match network.mode {
+                    networking::Mode::MultiplayerPlayer1 => {
+                        if event_pump.mouse_state().left() {
+                            if throwing_portal {
+                                player.portal.throw_potion(0, mouse_x, mouse_y);
+                            } else {
+                                player.portal.open_portal(clock.millis(), 0);
+                            }
+                        }
+                        let remote_portal = remote_player.unwrap().portal_data;
+                        if remote_portal.0 != 0.0 && remote_portal.1 != 0.0 {
+                            player.portal.portals[1].open(remote_portal.0, remote_portal.1, remote_portal.2);
+                        }
+                    },
+                    networking::Mode::MultiplayerPlayer2 => {
+                        if event_pump.mouse_state().left() {
+                            if throwing_portal {
+                                player.portal.throw_potion(1, mouse_x, mouse_y);
+                            } else {
+                                player.portal.open_portal(clock.millis(), 1);
+                            }
+                        }
+                        let remote_portal = remote_player.unwrap().portal_data;
+                        if remote_portal.0 != 0.0 && remote_portal.1 != 0.0 {
+                            player.portal.portals[0].open(remote_portal.0, remote_portal.1, remote_portal.2);
+                        }
+                    }
+                }
+            } else {
+                if event_pump.mouse_state().left() {
+                    if throwing_portal {
+                        player.portal.throw_potion(0, mouse_x, mouse_y);
+                    } else {
+                        player.portal.open_portal(clock.millis(), 0);
+                    }
+                }
+                if event_pump.mouse_state().right() {
+                    if throwing_portal {
+                        player.portal.throw_potion(1, mouse_x, mouse_y);
+                    } else {
+                        player.portal.open_portal(clock.millis(), 1);
+                    }
+                }
             }
-        } else {
-            // check to see if player has reached the end of the level
-            if level_cleared_time.is_none() && player.collider.is_touching(&door_collider) {
-                level_cleared_time = Some(Instant::now());
-                player.stop();
+            /*
+            Level cleared Logic
+             */
+            if remote_player.is_some() {
+                let remote_player = remote_player.unwrap().player_data;
+                let remote_player_collider = RectCollider::new(remote_player.0, remote_player.1, 69.0, 98.0);
+                if level_cleared_time.is_none() && touching_door(&player.collider, &contents.doors, &contents.logic) && touching_door(&remote_player_collider, &contents.doors, &contents.logic) {
+                    level_cleared_time = Some(Instant::now());
+                    player.stop();
+                }
+            } else {
+                // check to see if player has reached the end of the level
+                if level_cleared_time.is_none() && touching_door(&player.collider, &contents.doors, &contents.logic) {
+                    level_cleared_time = Some(Instant::now());
+                    player.stop();
+                }
             }
+            /*
+            End game state update.
+             */
+            clock.tick();
+            lag -= TICK_TIME;
         }
-        /*
-        End game state update.
-         */
+        // draw everything part of the way from where it was one tick ago to where it is now
+        let frame = previous.lerp(&Snapshot::take(&player, &contents), lag.as_secs_f32() / TICK_TIME.as_secs_f32());
 
         // **********************************************************************
         /*
@@ -531,38 +605,57 @@ pub(crate) fn run(mut wincan: WindowCanvas, mut event_pump: sdl2::EventPump,
 
         wincan.copy(&castle_bg, None, None).ok();
 
-        draw_level_cleared_door(&mut wincan, &door_sheet, &player, &door_collider, &multiplayer, &remote_player_collider);
-        // draw_collision_boxes(&mut wincan, &player1);
+        // everything in the level is drawn where it is on screen
+        let cam = contents.camera.at(frame.camera);
+        let (mouse_x, mouse_y) = cam.to_world(event_pump.mouse_state().x(), event_pump.mouse_state().y());
+        let players = player_colliders(&player, remote_player);
+        for door in &contents.doors {
+            draw_level_cleared_door(wincan, &cam, &door_sheet, door, contents.logic.is_on(door.wire), &players);
+        }
+        // draw_collision_boxes(wincan, &player1);
         // draw the surfaces
-        for obj in level.iter() {
-            let mut draw_surface = |surface_texture: &Texture| {
-                draw_surface(
-                    &mut wincan,
-                    surface_texture,
-                    obj[1].parse().unwrap(),
-                    obj[2].parse().unwrap(),
-                    obj[3].parse().unwrap(),
-                    obj[4].parse().unwrap()
-                );
-            };
-            if obj[0] == "portalblock" {
-                draw_surface(&portal_surface);
-            }
-            if obj[0] == "nonportalblock" {
-                draw_surface(&nonportal_surface);
-            }
-            if obj[0] == "portalglass" {
-                draw_surface(&portal_glass);
-            }
-            if obj[0] == "gateplate" {
-                draw_plate(&mut wincan, &pressure_plate, platecon);
-                draw_gate(&mut wincan, &gate, platecon)
+        for obj in level.objects.iter() {
+            match obj {
+                LevelObject::PortalBlock(surface) => draw_surface(wincan, &cam, &portal_surface, surface),
+                LevelObject::NonPortalBlock(surface) => draw_surface(wincan, &cam, &nonportal_surface, surface),
+                LevelObject::PortalGlass(surface) => draw_surface(wincan, &cam, &portal_glass, surface),
+                LevelObject::Slope { surface, rises_right } => draw_slope(wincan, &cam, &slope_texture, surface, *rises_right),
+                LevelObject::Hazard(hazard) => {
+                    let sprite = match hazard.kind {
+                        HazardKind::Spikes => &spikes_texture,
+                        HazardKind::Lava => &lava_texture,
+                        HazardKind::Acid => &acid_texture,
+                    };
+                    draw_surface(wincan, &cam, sprite, &hazard.surface);
+                }
+                // platforms are drawn wherever they've moved to, and gates and lights however they're wired, below
+                _ => {}
             }
         }
+        for (platform, &(x, y)) in contents.platforms.iter().zip(&frame.platforms) {
+            let collider = RectCollider::new(x, y, platform.collider().width(), platform.collider().height());
+            draw_platform(wincan, &cam, if platform.portal() { &portal_surface } else { &nonportal_surface }, &collider);
+        }
+        draw_plate(wincan, &cam, &pressure_plate, &contents.plates);
+        draw_gate(wincan, &cam, &gate, &contents.gates);
+        for button in &contents.buttons {
+            draw_button(wincan, &cam, &button_texture, button, clock.millis());
+        }
+        for &(wire, x, y) in &contents.lights {
+            draw_light(wincan, &cam, &light_texture, x, y, contents.logic.is_on(wire));
+        }
 
-        draw_block(&mut wincan, &block, &block_texture);
+        for checkpoint in &contents.checkpoints {
+            draw_checkpoint(wincan, &cam, &checkpoint_texture, checkpoint.collider().x() as i32, checkpoint.collider().y() as i32, checkpoint.reached());
+        }
+        for &position in &frame.blocks {
+            draw_block(wincan, &cam, position, &block_texture);
+        }
+        for (&position, ball) in frame.balls.iter().zip(&contents.balls) {
+            draw_ball(wincan, &cam, position, ball.rotation());
+        }
 
-        render_player(&p1sprite, &mut wincan, &mut player, &multiplayer)?;
+        render_player(&p1sprite, wincan, &cam, frame.player, &mut player, multiplayer)?;
         match remote_player {
             Some(_) => {
                 let player_data = remote_player.unwrap().player_data;
@@ -574,17 +667,24 @@ pub(crate) fn run(mut wincan: WindowCanvas, mut event_pump: sdl2::EventPump,
                     player_data.5,
                     player_data.6
                 );
-                render_remote_player(&mut wincan, &p1sprite, player_pos, flip, anim_rect)?;
+                render_remote_player(wincan, &cam, &p1sprite, player_pos, flip, anim_rect)?;
             }
             None => {}
         }
 
-        let mut render_portal = |p: &Portal| {
-            wincan.copy_ex(&portalsprite, Rect::new(500 * p.color() + 125, 0, 125, 250), Rect::new(p.x() as i32, p.y() as i32, 60, 100), p.rotation().into(), None, false, false).unwrap();
+        // the grids shimmer over whatever's going through them
+        for obj in level.objects.iter() {
+            if let LevelObject::Grid(surface) = obj {
+                draw_grid(wincan, &cam, surface, clock.millis());
+            }
+        }
+
+        let mut render_portal = |p: &Portal, (x, y): (f32, f32)| {
+            wincan.copy_ex(&portalsprite, Rect::new(500 * p.color() + 125, 0, 125, 250), cam.to_screen(Rect::new(x as i32, y as i32, 60, 100)), p.rotation().into(), None, false, false).unwrap();
         };
         // render portals
-        for p in &player.portal.portals {
-            render_portal(p);
+        for (p, &position) in player.portal.portals.iter().zip(&frame.portals) {
+            render_portal(p, position);
         }
 
         //Wand Rendering
@@ -595,16 +695,16 @@ pub(crate) fn run(mut wincan: WindowCanvas, mut event_pump: sdl2::EventPump,
                 let network = multiplayer.as_ref().unwrap();
                 match network.mode {
                     networking::Mode::MultiplayerPlayer1 => {
-                        wincan.copy_ex(&bluewand , None, Rect::new(player.physics.x() as i32 + player.portal.wand_x(), player.physics.y() as i32 + player.portal.wand_y(), 100, 20), player.portal.next_rotation(event_pump.mouse_state().x(), event_pump.mouse_state().y()).into(), None, false, false)?;
-                        wincan.copy_ex(&orangewand, None, Rect::new(player_data.0 as i32 + wand_data.0, player_data.1 as i32 + wand_data.1, 100, 20), wand_data.2 as f64, None, false, false)?;
+                        wincan.copy_ex(&bluewand , None, cam.to_screen(Rect::new(frame.player.0 as i32 + player.portal.wand_x(), frame.player.1 as i32 + player.portal.wand_y(), 100, 20)), player.portal.next_rotation(mouse_x, mouse_y).into(), None, false, false)?;
+                        wincan.copy_ex(&orangewand, None, cam.to_screen(Rect::new(player_data.0 as i32 + wand_data.0, player_data.1 as i32 + wand_data.1, 100, 20)), wand_data.2 as f64, None, false, false)?;
                     }
                     networking::Mode::MultiplayerPlayer2 => {
-                        wincan.copy_ex(&orangewand , None, Rect::new(player.physics.x() as i32 + player.portal.wand_x(), player.physics.y() as i32 + player.portal.wand_y(), 100, 20), player.portal.next_rotation(event_pump.mouse_state().x(), event_pump.mouse_state().y()).into(), None, false, false)?;
-                        wincan.copy_ex(&bluewand, None, Rect::new(player_data.0 as i32 + wand_data.0, player_data.1 as i32 + wand_data.1, 100, 20), wand_data.2 as f64, None, false, false)?;
+                        wincan.copy_ex(&orangewand , None, cam.to_screen(Rect::new(frame.player.0 as i32 + player.portal.wand_x(), frame.player.1 as i32 + player.portal.wand_y(), 100, 20)), player.portal.next_rotation(mouse_x, mouse_y).into(), None, false, false)?;
+                        wincan.copy_ex(&bluewand, None, cam.to_screen(Rect::new(player_data.0 as i32 + wand_data.0, player_data.1 as i32 + wand_data.1, 100, 20)), wand_data.2 as f64, None, false, false)?;
                     }
                 }
             }
-            None => { /*wincan.copy_ex(if player.portal.last_portal() == 0 { &bluewand } else { &orangewand }, None, Rect::new(player.physics.x() as i32 + player.portal.wand_x(), player.physics.y() as i32 + player.portal.wand_y(), 100, 20), player.portal.next_rotation(event_pump.mouse_state().x(), event_pump.mouse_state().y()).into(), None, false, false)?;*/ }
+            None => { /*wincan.copy_ex(if player.portal.last_portal() == 0 { &bluewand } else { &orangewand }, None, cam.to_screen(Rect::new(frame.player.0 as i32 + player.portal.wand_x(), frame.player.1 as i32 + player.portal.wand_y(), 100, 20)), player.portal.next_rotation(mouse_x, mouse_y).into(), None, false, false)?;*/ }
         }
 
         // render potions as they fly through the air
@@ -621,31 +721,29 @@ pub(crate) fn run(mut wincan: WindowCanvas, mut event_pump: sdl2::EventPump,
                 let y = potion_data.1;
                 let r = potion_data.2;
                 let r = r as f64;
-                wincan.copy_ex(&potionsprite, source, Rect::new((x-12.5) as i32, (y-12.5) as i32, 25, 25), r, None, false, false)?;
+                wincan.copy_ex(&potionsprite, source, cam.to_screen(Rect::new((x-12.5) as i32, (y-12.5) as i32, 25, 25)), r, None, false, false)?;
             }
         }
       
         let mut potion_state = player.portal.get_potion_state();
         if potion_state.0.is_some() {
             let p0state = potion_state.0.unwrap();
-            let p0x = p0state.0;
-            let p0y = p0state.1;
+            let (p0x, p0y) = frame.potions[0];
             let p0r = p0state.2;
-            wincan.copy_ex(&potionsprite, Rect::new(417, 0, 417, 417), Rect::new((p0x-12.5) as i32, (p0y-12.5) as i32, 25, 25), p0r, None, false, false)?;
+            wincan.copy_ex(&potionsprite, Rect::new(417, 0, 417, 417), cam.to_screen(Rect::new((p0x-12.5) as i32, (p0y-12.5) as i32, 25, 25)), p0r, None, false, false)?;
         }
         if potion_state.1.is_some() {
             let p1state = potion_state.1.unwrap();
-            let p1x = p1state.0;
-            let p1y = p1state.1;
+            let (p1x, p1y) = frame.potions[1];
             let p1r = p1state.2;
-            wincan.copy_ex(&potionsprite, Rect::new(0, 0, 417, 417), Rect::new((p1x-12.5) as i32, (p1y-12.5) as i32, 25, 25), p1r, None, false, false)?;
+            wincan.copy_ex(&potionsprite, Rect::new(0, 0, 417, 417), cam.to_screen(Rect::new((p1x-12.5) as i32, (p1y-12.5) as i32, 25, 25)), p1r, None, false, false)?;
         }
 
         // wand and potions
         if throwing_portal {
-            wincan.copy(&potionsprite, Rect::new((1-player.portal.last_portal()) as i32 *417, 0, 417, 417), Rect::new(player.physics.x() as i32 + player.portal.potion_x(), player.physics.y() as i32 + player.portal.potion_y(), 25, 25))?;
+            wincan.copy(&potionsprite, Rect::new((1-player.portal.last_portal()) as i32 *417, 0, 417, 417), cam.to_screen(Rect::new(frame.player.0 as i32 + player.portal.potion_x(), frame.player.1 as i32 + player.portal.potion_y(), 25, 25)))?;
         } else {
-            wincan.copy_ex(if player.portal.last_portal() == 0 { &bluewand } else { &orangewand }, None, Rect::new(player.physics.x() as i32 + player.portal.wand_x(), player.physics.y() as i32 + player.portal.wand_y(), 100, 20), player.portal.next_rotation(event_pump.mouse_state().x(), event_pump.mouse_state().y()).into(), None, false, false)?;
+            wincan.copy_ex(if player.portal.last_portal() == 0 { &bluewand } else { &orangewand }, None, cam.to_screen(Rect::new(frame.player.0 as i32 + player.portal.wand_x(), frame.player.1 as i32 + player.portal.wand_y(), 100, 20)), player.portal.next_rotation(mouse_x, mouse_y).into(), None, false, false)?;
         }
         // the pause screen
         if paused {
@@ -665,28 +763,220 @@ pub(crate) fn run(mut wincan: WindowCanvas, mut event_pump: sdl2::EventPump,
         End rendering current frame.
          */
 
-        let duration_to_sleep = FRAME_TIME.checked_sub(tick.elapsed());
+        let duration_to_sleep = MIN_FRAME_TIME.checked_sub(tick.elapsed());
         if duration_to_sleep.is_some() {
             // println!("elapsed time: {:?}\nduration should sleep: {:?}", tick.elapsed(), duration);
             thread::sleep(duration_to_sleep.unwrap());
         }
     } // end game_loop
 
-    credits::show_credits(wincan, event_pump);
     Ok(())
 }
 
-fn render_player(texture: &Texture, wincan: &mut WindowCanvas, player1: &mut Player, network: &Option<Multiplayer>) -> Result<(), String>{
-    let pos_rect = player1.physics.position_rect();
-    let pos_rect = Rect::new(pos_rect.0, pos_rect.1, pos_rect.2, pos_rect.3);
+// LevelContents: everything in the current level that can move or change as it's played, besides the player
+#[derive(Default)]
+struct LevelContents {
+    blocks: Vec<ObjectController>,
+    balls: Vec<BallController>,
+    plates: Vec<PlateController>,
+    buttons: Vec<ButtonController>,
+    gates: Vec<GateController>,
+    // every exit in the level
+    doors: Vec<Door>,
+    // every spike pit, lava pool and acid pool in the level
+    hazards: Vec<Hazard>,
+    platforms: Vec<PlatformController>,
+    checkpoints: Vec<CheckpointController>,
+    // the wire each light is on, and where it is
+    lights: Vec<(u32, i32, i32)>,
+    logic: LogicController,
+    // the part of the level that's on screen
+    camera: Camera,
+}
+
+// Snapshot: where everything that moves was at the end of a tick. frames drawn between two ticks put things
+// part of the way from one snapshot to the next, so movement looks smooth at any refresh rate
+#[derive(Clone)]
+struct Snapshot {
+    camera: (f32, f32),
+    player: (f32, f32),
+    blocks: Vec<(f32, f32)>,
+    // the middle of each ball
+    balls: Vec<(f32, f32)>,
+    platforms: Vec<(f32, f32)>,
+    portals: Vec<(f32, f32)>,
+    potions: Vec<(f32, f32)>,
+}
+
+impl Snapshot {
+    fn take(player: &Player, contents: &LevelContents) -> Snapshot {
+        Snapshot {
+            camera: contents.camera.position(),
+            player: (player.physics.x(), player.physics.y()),
+            blocks: contents.blocks.iter().map(|b| (b.collider().x(), b.collider().y())).collect(),
+            balls: contents.balls.iter().map(|b| (b.collider().x(), b.collider().y())).collect(),
+            platforms: contents.platforms.iter().map(|p| (p.collider().x(), p.collider().y())).collect(),
+            portals: player.portal.portals.iter().map(|p| (p.x(), p.y())).collect(),
+            potions: player.portal.potions.iter().map(|p| (p.x(), p.y())).collect(),
+        }
+    }
+
+    // lerp: the snapshot alpha of the way from this one to the next (0 is this one, 1 is the next)
+    fn lerp(&self, next: &Snapshot, alpha: f32) -> Snapshot {
+        let point = |from: (f32, f32), to: (f32, f32)| {
+            if (to.0 - from.0).abs() > SNAP_DISTANCE || (to.1 - from.1).abs() > SNAP_DISTANCE {
+                to
+            } else {
+                (from.0 + (to.0 - from.0) * alpha, from.1 + (to.1 - from.1) * alpha)
+            }
+        };
+        // after a level change the two snapshots aren't of the same things, so just use the new one
+        let points = |from: &[(f32, f32)], to: &[(f32, f32)]| -> Vec<(f32, f32)> {
+            if from.len() == to.len() {
+                from.iter().zip(to).map(|(&a, &b)| point(a, b)).collect()
+            } else {
+                to.to_vec()
+            }
+        };
+        Snapshot {
+            camera: point(self.camera, next.camera),
+            player: point(self.player, next.player),
+            blocks: points(&self.blocks, &next.blocks),
+            balls: points(&self.balls, &next.balls),
+            platforms: points(&self.platforms, &next.platforms),
+            portals: points(&self.portals, &next.portals),
+            potions: points(&self.potions, &next.potions),
+        }
+    }
+}
+
+// load_level: swaps in the colliders, start points, gates and wiring for a freshly parsed level
+fn load_level(level: &Level, player: &mut Player, contents: &mut LevelContents) {
+    player.reset_colliders();
+    player.restrict(&level.rules());
+    let (level_width, level_height) = level.size();
+    player.set_level_size(level_width, level_height);
+    *contents = LevelContents::default();
+    contents.camera = Camera::new(level_width, level_height);
+    // each gate/plate combo gets a wire of its own, numbered after the ones the level uses
+    let mut combo_wire = level.objects.iter()
+        .flat_map(|o| o.wire_out().into_iter().chain(o.wires_in()))
+        .max()
+        .unwrap_or(0);
+    for obj in level.objects.iter() {
+        match *obj {
+            LevelObject::Start { x, y } => {
+                player.physics.set_start_x(x as f32);
+                player.physics.set_start_y(y as f32);
+                player.respawn();
+            }
+            LevelObject::Checkpoint { x, y, save_blocks } => contents.checkpoints.push(CheckpointController::new(x, y, save_blocks)),
+            LevelObject::Block { x, y } => {
+                let mut block = ObjectController::new(RectCollider::new(x as f32, y as f32, (TILE_SIZE/2) as f32, (TILE_SIZE/2) as f32));
+                block.set_start_pos(x as f32, y as f32);
+                block.respawn();
+                contents.blocks.push(block);
+            }
+            LevelObject::Ball { x, y } => contents.balls.push(BallController::new(x as f32, y as f32)),
+            // the rules and size were already taken care of
+            LevelObject::Rules(_) | LevelObject::Size { .. } => {}
+            LevelObject::PortalBlock(surface) => player.add_collider(surface.collider(), "portalblock"),
+            LevelObject::NonPortalBlock(surface) => player.add_collider(surface.collider(), "nonportalblock"),
+            LevelObject::PortalGlass(surface) => player.add_collider(surface.collider(), "portalglass"),
+            LevelObject::Grid(surface) => player.add_collider(surface.collider(), "grid"),
+            LevelObject::Slope { surface, rises_right } => player.add_slope(surface.slope_collider(rises_right)),
+            LevelObject::GatePlate { plate_x, plate_y, gate_x, gate_y, gate_length, gate_vertical } => {
+                combo_wire += 1;
+                contents.plates.push(PlateController::new(plate_x, plate_y, combo_wire, false));
+                contents.gates.push(GateController::new(gate_x, gate_y, gate_length, gate_vertical, combo_wire));
+            }
+            LevelObject::Door(door) => contents.doors.push(door),
+            LevelObject::Hazard(hazard) => contents.hazards.push(hazard),
+            LevelObject::Platform(ref platform) => {
+                let waypoints = platform.waypoints.iter().map(|&(x, y)| (x as f32, y as f32)).collect();
+                contents.platforms.push(PlatformController::new(platform.surface.collider(), waypoints, platform.speed as f32, platform.wire, platform.portal));
+            }
+            LevelObject::Plate { wire, x, y, players } => contents.plates.push(PlateController::new(x, y, wire, players)),
+            LevelObject::Button { wire, x, y, ms } => contents.buttons.push(ButtonController::new(x, y, wire, ms)),
+            LevelObject::Timer { wire, on, off } => contents.logic.add_timer(wire, on, off),
+            LevelObject::Zone { wire, x, y, width, height } => {
+                contents.logic.add_zone(wire, RectCollider::new(x as f32, y as f32, width as f32, height as f32));
+            }
+            LevelObject::Logic { wire, ref node } => contents.logic.add_node(wire, node.clone()),
+            LevelObject::Gate { wire, x, y, length, vertical } => contents.gates.push(GateController::new(x, y, length, vertical, wire)),
+            LevelObject::Light { wire, x, y } => contents.lights.push((wire, x, y)),
+        }
+    }
+    if contents.doors.is_empty() {
+        contents.doors.push(Door::default_exit(level_width, level_height));
+    }
+    // start with the player in view
+    contents.camera.follow(&player.collider);
+    // blocks land on the same surfaces the player does
+    for block in contents.blocks.iter_mut() {
+        block.set_level_width(level_width);
+        for c in player.physics.colliders() {
+            block.add_collider(c);
+        }
+        for s in player.physics.slopes() {
+            block.add_slope(s);
+        }
+    }
+    // and so do balls
+    for ball in contents.balls.iter_mut() {
+        ball.set_level_width(level_width);
+        for c in player.physics.colliders() {
+            ball.add_collider(c);
+        }
+        for s in player.physics.slopes() {
+            ball.add_slope(s);
+        }
+    }
+}
+
+// open_level: parses the next level in the pack and puts its name in the title bar
+fn open_level(wincan: &mut WindowCanvas, entry: &PackEntry) -> Result<Level, String> {
+    let level = match levels::parse_level(&entry.file) {
+        Ok(level) => level,
+        Err(e) => return Err(show_level_error(wincan, &e)),
+    };
+    set_level_title(wincan, entry, &level, 0);
+    Ok(level)
+}
+
+// set_level_title: shows the level's name in the title bar, what the level doesn't let the player do,
+// and how many times the player has died on it
+fn set_level_title(wincan: &mut WindowCanvas, entry: &PackEntry, level: &Level, deaths: u32) {
+    let mut title = format!("Warp Wizards - {}", entry.display_name);
+    let rules: Vec<&str> = level.rules().iter().map(|r| r.description()).collect();
+    if !rules.is_empty() {
+        title.push_str(&format!(" - {}", rules.join(", ")));
+    }
+    if deaths > 0 {
+        title.push_str(&format!(" - deaths: {}", deaths));
+    }
+    wincan.window_mut().set_title(&title).ok();
+}
+
+// show_level_error: tells the player a level file is broken instead of crashing
+pub(crate) fn show_level_error(wincan: &WindowCanvas, error: &LevelError) -> String {
+    let message = error.to_string();
+    eprintln!("{}", message);
+    show_simple_message_box(MessageBoxFlag::ERROR, "Could not load level", &message, wincan.window()).ok();
+    message
+}
+
+// render_player: draws the player at a spot between ticks, see Snapshot
+fn render_player(texture: &Texture, wincan: &mut WindowCanvas, cam: &Camera, position: (f32, f32), player1: &mut Player, network: &Option<Multiplayer>) -> Result<(), String>{
+    let pos_rect = cam.to_screen(Rect::new(position.0 as i32, position.1 as i32, 69, 98));
     wincan.copy_ex(&texture, player1.anim.next_anim(network), pos_rect, 0.0, None, player1.flip_horizontal, false)
 }
 
-fn render_remote_player(wincan: &mut WindowCanvas, player_sprite: &Texture, player_pos: (f32, f32), flip: bool, anim_rect: Rect) -> Result<(), String> {
-    wincan.copy_ex(player_sprite, anim_rect, Rect::new(player_pos.0 as i32, player_pos.1 as i32, 69, 98), 0.0, None, flip, false)
+fn render_remote_player(wincan: &mut WindowCanvas, cam: &Camera, player_sprite: &Texture, player_pos: (f32, f32), flip: bool, anim_rect: Rect) -> Result<(), String> {
+    wincan.copy_ex(player_sprite, anim_rect, cam.to_screen(Rect::new(player_pos.0 as i32, player_pos.1 as i32, 69, 98)), 0.0, None, flip, false)
 }
 
-fn move_player(player: &mut Player, keystate: &HashSet<Keycode>) {
+fn move_player(player: &mut Player, keystate: &HashSet<Keycode>, time: u128) {
     if keystate.contains(&Keycode::A) {
         player.physics.accelerate_left();
     }
@@ -694,72 +984,187 @@ fn move_player(player: &mut Player, keystate: &HashSet<Keycode>) {
         player.physics.accelerate_right();
     }
     if keystate.contains(&Keycode::W) || keystate.contains(&Keycode::Space) {
-        player.physics.jump();
+        player.physics.jump(time);
     }
 }
 
-fn draw_block(wincan: &mut WindowCanvas, block: &ObjectController, sprite: &Texture) {
+fn draw_block(wincan: &mut WindowCanvas, cam: &Camera, position: (f32, f32), sprite: &Texture) {
     wincan.set_draw_color(Color::RGBA(255, 0, 0, 255));
-    wincan.copy(sprite, None, Rect::new(block.x() as i32, block.y() as i32, TILE_SIZE/2, TILE_SIZE/2)).ok();
+    wincan.copy(sprite, None, cam.to_screen(Rect::new(position.0 as i32, position.1 as i32, TILE_SIZE/2, TILE_SIZE/2))).ok();
 }
 
-fn draw_surface(wincan: &mut WindowCanvas, sprite: &Texture, x: i32, y: i32, width: i32, height: i32) {
-    for i in 0..width {
-        for j in 0..height {
-            wincan.copy(sprite, None, Rect::new(x+i*TILE_SIZE as i32, y+j*TILE_SIZE as i32, TILE_SIZE, TILE_SIZE)).ok();
-        }
+// draw_ball: a ball with its middle at the position, with a spoke across it so it can be seen rolling. there's no
+// sprite for it, so it's drawn a row at a time
+pub(crate) fn draw_ball(wincan: &mut WindowCanvas, cam: &Camera, position: (f32, f32), rotation: f32) {
+    let middle = cam.to_screen(Rect::new(position.0 as i32, position.1 as i32, 1, 1));
+    let (mx, my, r) = (middle.x(), middle.y(), RADIUS as i32);
+    wincan.set_draw_color(Color::RGBA(200, 120, 40, 255));
+    for dy in -r..=r {
+        let half = ((r*r - dy*dy) as f32).powf(0.5) as i32;
+        wincan.draw_line((mx - half, my + dy), (mx + half, my + dy)).ok();
     }
+    let spoke = (rotation / 57.29).cos() * RADIUS;
+    let spoke_y = (rotation / 57.29).sin() * RADIUS;
+    wincan.set_draw_color(Color::RGBA(90, 50, 10, 255));
+    wincan.draw_line((mx - spoke as i32, my - spoke_y as i32), (mx + spoke as i32, my + spoke_y as i32)).ok();
 }
 
-fn draw_plate(wincan: &mut WindowCanvas, sprite: &Texture, platecon: PlateController) {
-    let x = platecon.plate_collider().x();
-    let y = platecon.plate_collider().y()-TILE_SIZE as f32/2.0;
-    if platecon.plate_pressed() {
-        wincan.copy(sprite, Rect::new(532, 0, 266, 266), Rect::new(x as i32, y as i32, TILE_SIZE, TILE_SIZE)).ok();
-    } else {
-        wincan.copy(sprite, Rect::new(0, 0, 266, 266), Rect::new(x as i32, y as i32, TILE_SIZE, TILE_SIZE)).ok();
+// player_colliders: the local player, and the remote one if there is one
+fn player_colliders(player: &Player, remote_player: Option<RemotePlayer>) -> Vec<RectCollider> {
+    let mut players = vec!(player.collider);
+    if let Some(remote) = remote_player {
+        players.push(RectCollider::new(remote.player_data.0, remote.player_data.1, 69.0, 98.0));
     }
+    players
 }
 
-fn draw_gate(wincan: &mut WindowCanvas, sprite: &Texture, platecon: PlateController) {
-    let x = platecon.gate_x();
-    let y = platecon.gate_y();
-    let length = platecon.gate_length();
-    if !platecon.gate_vertical() {
-        if !platecon.plate_pressed() {
-            wincan.copy(sprite, Rect::new(266, 0, 266, 266), Rect::new(x as i32, y as i32, length.try_into().unwrap(), TILE_SIZE)).ok();
+pub(crate) fn draw_surface(wincan: &mut WindowCanvas, cam: &Camera, sprite: &Texture, surface: &Surface) {
+    for i in 0..surface.width as i32 {
+        for j in 0..surface.height as i32 {
+            wincan.copy(sprite, None, cam.to_screen(Rect::new(surface.x+i*TILE_SIZE as i32, surface.y+j*TILE_SIZE as i32, TILE_SIZE, TILE_SIZE))).ok();
         }
-        wincan.copy(sprite, Rect::new(0, 0, 133, 266), Rect::new(x as i32, y as i32, TILE_SIZE/2, TILE_SIZE)).ok();
-        wincan.copy(sprite, Rect::new(133, 0, 133, 266), Rect::new(x as i32+length-(TILE_SIZE as i32)/2, y as i32, TILE_SIZE/2, TILE_SIZE)).ok();
+    }
+}
+
+// draw_grid: a see-through field that slowly pulses, with bright lines drifting up through it
+pub(crate) fn draw_grid(wincan: &mut WindowCanvas, cam: &Camera, surface: &Surface, time: u128) {
+    let area = cam.to_screen(Rect::new(surface.x, surface.y, surface.width * TILE_SIZE, surface.height * TILE_SIZE));
+    let blend = wincan.blend_mode();
+    wincan.set_blend_mode(BlendMode::Blend);
+    let pulse = ((time % 2000) as f32 / 2000.0 * std::f32::consts::PI * 2.0).sin();
+    wincan.set_draw_color(Color::RGBA(120, 200, 255, (60.0 + 20.0 * pulse) as u8));
+    wincan.fill_rect(area).ok();
+    wincan.set_draw_color(Color::RGBA(200, 240, 255, 140));
+    let offset = (time / 40 % 16) as i32;
+    let mut y = area.bottom() - offset;
+    while y > area.top() {
+        wincan.draw_line((area.left(), y), (area.right() - 1, y)).ok();
+        y -= 16;
+    }
+    // the emitters along the edges the field runs between
+    wincan.set_draw_color(Color::RGBA(80, 90, 110, 255));
+    if surface.width >= surface.height {
+        wincan.fill_rect(Rect::new(area.left(), area.top(), 4, area.height())).ok();
+        wincan.fill_rect(Rect::new(area.right() - 4, area.top(), 4, area.height())).ok();
     } else {
-        if !platecon.plate_pressed() {
-            wincan.copy_ex(sprite, Rect::new(266, 0, 266, 266), Rect::new(x as i32-length/2+TILE_SIZE as i32/2, y as i32+length/2-TILE_SIZE as i32/2, length.try_into().unwrap(), TILE_SIZE), 90.0, None, false, false).ok();
-        }
-        wincan.copy_ex(sprite, Rect::new(0, 0, 133, 266), Rect::new(x as i32+16, y as i32-16, TILE_SIZE/2, TILE_SIZE), 90.0, None, false, false).ok();
-        wincan.copy_ex(sprite, Rect::new(133, 0, 133, 266), Rect::new(x as i32+16, y as i32-16+length-(TILE_SIZE as i32)/2, TILE_SIZE/2, TILE_SIZE), 90.0, None, false, false).ok();
+        wincan.fill_rect(Rect::new(area.left(), area.top(), area.width(), 4)).ok();
+        wincan.fill_rect(Rect::new(area.left(), area.bottom() - 4, area.width(), 4)).ok();
     }
+    wincan.set_blend_mode(blend);
 }
 
+// draw_platform: draws a moving platform wherever it is right now
+pub(crate) fn draw_platform(wincan: &mut WindowCanvas, cam: &Camera, sprite: &Texture, collider: &RectCollider) {
+    let surface = Surface {
+        x: collider.x().round() as i32,
+        y: collider.y().round() as i32,
+        width: collider.width() as u32 / TILE_SIZE,
+        height: collider.height() as u32 / TILE_SIZE,
+    };
+    draw_surface(wincan, cam, sprite, &surface);
+}
 
-fn draw_level_cleared_door(wincan: &mut WindowCanvas, door_sheet: &Texture, player: &Player, door_collider: &RectCollider, network: &Option<Multiplayer>, remote_collider: &RectCollider) {
-    let pos = Rect::new((1280 - DOORW) as i32, (720 - 64 - DOORH) as i32, DOORW, DOORH);
-    let src: Rect;
-    if network.is_some() {
-        if player.collider.is_touching(door_collider) || remote_collider.is_touching(door_collider){
-            // get open door
-            src = Rect::new(DOORW as i32, 0, DOORW, DOORH);
+// draw_slope: draws a slope as thin columns of its texture, each one cut off where the slope is
+pub(crate) fn draw_slope(wincan: &mut WindowCanvas, cam: &Camera, sprite: &Texture, surface: &Surface, rises_right: bool) {
+    const STRIP: i32 = 2;
+    let tile = TILE_SIZE as i32;
+    let slope = surface.slope_collider(rises_right);
+    let bottom = surface.y + surface.height as i32 * tile;
+    for col in (0..surface.width as i32 * tile).step_by(STRIP as usize) {
+        let top = slope.surface_y((surface.x + col) as f32 + STRIP as f32/2.0).unwrap().round() as i32;
+        // the texture's bricks run up to the right, so mirror it for slopes that go the other way
+        let src_x = if rises_right { col % tile } else { tile - STRIP - col % tile };
+        // go a tile at a time so the texture lines up with the tiles around it
+        let mut y = top;
+        while y < bottom {
+            let src_y = (y - surface.y) % tile;
+            let height = (tile - src_y).min(bottom - y);
+            wincan.copy_ex(sprite, Rect::new(src_x, src_y, STRIP as u32, height as u32), cam.to_screen(Rect::new(surface.x + col, y, STRIP as u32, height as u32)), 0.0, None, !rises_right, false).ok();
+            y += height;
+        }
+    }
+}
+
+pub(crate) fn draw_plate(wincan: &mut WindowCanvas, cam: &Camera, sprite: &Texture, plates: &[PlateController]) {
+    for platecon in plates {
+        let x = platecon.plate_collider().x();
+        let y = platecon.plate_collider().y()-TILE_SIZE as f32/2.0;
+        let pos = cam.to_screen(Rect::new(x as i32, y as i32, TILE_SIZE, TILE_SIZE));
+        if platecon.plate_pressed() {
+            wincan.copy(sprite, Rect::new(532, 0, 266, 266), pos).ok();
         } else {
-            // get closed door
-            src = Rect::new(0, 0, DOORW, DOORH);
+            wincan.copy(sprite, Rect::new(0, 0, 266, 266), pos).ok();
         }
-    } else {
-        if player.collider.is_touching(door_collider) {
-            // get open door
-            src = Rect::new(DOORW as i32, 0, DOORW, DOORH);
+    }
+}
+
+pub(crate) fn draw_gate(wincan: &mut WindowCanvas, cam: &Camera, sprite: &Texture, gates: &[GateController]) {
+    for gatecon in gates {
+        let x = gatecon.x();
+        let y = gatecon.y();
+        let length = gatecon.length();
+        if !gatecon.vertical() {
+            if !gatecon.is_open() {
+                wincan.copy(sprite, Rect::new(266, 0, 266, 266), cam.to_screen(Rect::new(x as i32, y as i32, length.try_into().unwrap(), TILE_SIZE))).ok();
+            }
+            wincan.copy(sprite, Rect::new(0, 0, 133, 266), cam.to_screen(Rect::new(x as i32, y as i32, TILE_SIZE/2, TILE_SIZE))).ok();
+            wincan.copy(sprite, Rect::new(133, 0, 133, 266), cam.to_screen(Rect::new(x as i32+length-(TILE_SIZE as i32)/2, y as i32, TILE_SIZE/2, TILE_SIZE))).ok();
         } else {
-            // get closed door
-            src = Rect::new(0, 0, DOORW, DOORH);
+            if !gatecon.is_open() {
+                wincan.copy_ex(sprite, Rect::new(266, 0, 266, 266), cam.to_screen(Rect::new(x as i32-length/2+TILE_SIZE as i32/2, y as i32+length/2-TILE_SIZE as i32/2, length.try_into().unwrap(), TILE_SIZE)), 90.0, None, false, false).ok();
+            }
+            wincan.copy_ex(sprite, Rect::new(0, 0, 133, 266), cam.to_screen(Rect::new(x as i32+16, y as i32-16, TILE_SIZE/2, TILE_SIZE)), 90.0, None, false, false).ok();
+            wincan.copy_ex(sprite, Rect::new(133, 0, 133, 266), cam.to_screen(Rect::new(x as i32+16, y as i32-16+length-(TILE_SIZE as i32)/2, TILE_SIZE/2, TILE_SIZE)), 90.0, None, false, false).ok();
         }
     }
+}
+
+
+fn touching_hazard(collider: &RectCollider, hazards: &[Hazard]) -> bool {
+    hazards.iter().any(|h| collider.is_touching(&h.collider()))
+}
+
+// draw_button: a wall button, pushed in while it's on, with a bar above it that runs down as its time runs out
+pub(crate) fn draw_button(wincan: &mut WindowCanvas, cam: &Camera, sprite: &Texture, button: &ButtonController, time: u128) {
+    let pos = cam.to_screen(Rect::new(button.collider().x() as i32, button.collider().y() as i32, TILE_SIZE, TILE_SIZE));
+    let (x, y) = (pos.x(), pos.y());
+    let frame = if button.is_on(time) { TILE_SIZE as i32 } else { 0 };
+    wincan.copy(sprite, Rect::new(frame, 0, TILE_SIZE, TILE_SIZE), pos).ok();
+    if let Some(left) = button.countdown(time) {
+        wincan.set_draw_color(Color::RGBA(40, 40, 40, 255));
+        wincan.fill_rect(Rect::new(x + 4, y - 10, TILE_SIZE - 8, 6)).ok();
+        wincan.set_draw_color(Color::RGBA(255, 220, 90, 255));
+        wincan.fill_rect(Rect::new(x + 4, y - 10, ((TILE_SIZE - 8) as f32 * left).ceil() as u32, 6)).ok();
+    }
+}
+
+// draw_checkpoint: a flag, raised once the player has reached it
+pub(crate) fn draw_checkpoint(wincan: &mut WindowCanvas, cam: &Camera, sprite: &Texture, x: i32, y: i32, reached: bool) {
+    let frame = if reached { TILE_SIZE as i32 } else { 0 };
+    wincan.copy(sprite, Rect::new(frame, 0, TILE_SIZE, TILE_SIZE*2), cam.to_screen(Rect::new(x, y, TILE_SIZE, TILE_SIZE*2))).ok();
+}
+
+// draw_light: a light, lit up or not
+pub(crate) fn draw_light(wincan: &mut WindowCanvas, cam: &Camera, sprite: &Texture, x: i32, y: i32, lit: bool) {
+    let frame = if lit { TILE_SIZE as i32 } else { 0 };
+    wincan.copy(sprite, Rect::new(frame, 0, TILE_SIZE, TILE_SIZE), cam.to_screen(Rect::new(x, y, TILE_SIZE, TILE_SIZE))).ok();
+}
+
+// touching_door: whether the collider is at a door that isn't locked
+fn touching_door(collider: &RectCollider, doors: &[Door], logic: &LogicController) -> bool {
+    doors.iter().any(|d| logic.is_on(d.wire) && collider.is_touching(&d.collider()))
+}
+
+// draw_level_cleared_door: a door, open while any player is at it (unless it's locked)
+fn draw_level_cleared_door(wincan: &mut WindowCanvas, cam: &Camera, door_sheet: &Texture, door: &Door, unlocked: bool, players: &[RectCollider]) {
+    let pos = cam.to_screen(Rect::new(door.x, door.y, door.width, door.height));
+    let door_collider = &door.collider();
+    let src = if unlocked && players.iter().any(|p| p.is_touching(door_collider)) {
+        // get open door
+        Rect::new(DOOR_WIDTH as i32, 0, DOOR_WIDTH, DOOR_HEIGHT)
+    } else {
+        // get closed door (locked doors stay shut)
+        Rect::new(0, 0, DOOR_WIDTH, DOOR_HEIGHT)
+    };
     wincan.copy(&door_sheet, src, pos).ok();
 }
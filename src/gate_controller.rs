@@ -0,0 +1,47 @@
+use crate::rect_collider::RectCollider;
+
+// GateController: a gate that blocks the way until its wire is turned on
+#[derive(Copy, Clone)]
+pub struct GateController {
+    x: i32,
+    y: i32,
+    length: i32,
+    vertical: bool,
+    wire: u32,
+    open: bool
+}
+
+impl GateController {
+    pub fn new(_x: i32, _y: i32, _length: i32, _vertical: bool, _wire: u32)
+        -> GateController
+    {
+        GateController {
+            x: _x,
+            y: _y,
+            length: _length,
+            vertical: _vertical,
+            wire: _wire,
+            open: false
+        }
+    }
+    pub fn x(&self) -> i32 { self.x }
+    pub fn y(&self) -> i32 { self.y }
+    pub fn length(&self) -> i32 { self.length }
+    pub fn vertical(&self) -> bool { self.vertical }
+    pub fn wire(&self) -> u32 { self.wire }
+    pub fn is_open(&self) -> bool { self.open }
+
+    pub fn set_open(&mut self, _open: bool) { self.open = _open; }
+
+    pub fn active_collider(&self) -> RectCollider {
+        if !self.open {
+            if self.vertical {
+                return RectCollider::new((self.x+6) as f32, self.y as f32, 44.0, self.length as f32);
+            } else {
+                return RectCollider::new(self.x as f32, (self.y+6) as f32, self.length as f32, 44.0);
+            }
+        }
+        // the gate is open, no active collider
+        RectCollider::new(0.0, 0.0, 0.0, 0.0)
+    }
+}
@@ -1,26 +1,890 @@
+use std::fmt;
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime};
 
-// load_level: used to load a level (UNUSED FOR NOW)
-pub(crate) fn parse_level(filename: &str) -> Vec<Vec<String>> {
-    //this function returns a list of the different objects in our scene
-    //separated into the different parameters for each object
-    let mut results: Vec<Vec<String>> = vec![];
-    let os_specific_linebreak = if std::env::consts::OS == "windows" {
-        "\r\n"
+use crate::rect_collider::RectCollider;
+use crate::resources;
+use crate::slope_collider::SlopeCollider;
+
+pub(crate) mod tiled;
+pub(crate) mod validator;
+
+// all tiles are 64x64 px
+pub const TILE_SIZE: u32 = 64;
+// the most tiles a surface can be across or down, so its size in pixels still fits in an i32
+pub const MAX_TILES: u32 = i32::MAX as u32 / TILE_SIZE;
+// the size of the window, and of any level that doesn't say how big it is
+pub const WINDOW_WIDTH: u32 = 1280;
+pub const WINDOW_HEIGHT: u32 = 720;
+// the size of one door in the door sprite sheet, used when a level doesn't give one
+pub const DOOR_WIDTH: u32 = 160;
+pub const DOOR_HEIGHT: u32 = 230;
+
+// Level: everything we read out of a level file
+pub struct Level {
+    pub objects: Vec<LevelObject>,
+}
+
+impl Level {
+    // size: how big the level is in pixels
+    pub fn size(&self) -> (u32, u32) {
+        self.objects.iter()
+            .rev()
+            .find_map(|o| match *o { LevelObject::Size { width, height } => Some((width, height)), _ => None })
+            .unwrap_or((WINDOW_WIDTH, WINDOW_HEIGHT))
+    }
+
+    // rules: everything the player isn't allowed to do on this level
+    pub fn rules(&self) -> Vec<Rule> {
+        self.objects.iter()
+            .filter_map(|o| match o { LevelObject::Rules(rules) => Some(rules), _ => None })
+            .flatten()
+            .copied()
+            .collect()
+    }
+}
+
+// LevelObject: one line of a level file (see instructions.txt for the format)
+#[derive(Clone, Debug, PartialEq)]
+pub enum LevelObject {
+    // how big the level is, for levels that scroll
+    Size { width: u32, height: u32 },
+    // the abilities the level takes away from the player
+    Rules(Vec<Rule>),
+    PortalBlock(Surface),
+    NonPortalBlock(Surface),
+    PortalGlass(Surface),
+    // an emancipation grid: walk through it and your portals close and the block you're carrying goes back to its start
+    Grid(Surface),
+    Slope { surface: Surface, rises_right: bool },
+    Hazard(Hazard),
+    Platform(Platform),
+    GatePlate { plate_x: i32, plate_y: i32, gate_x: i32, gate_y: i32, gate_length: i32, gate_vertical: bool },
+    Start { x: i32, y: i32 },
+    Checkpoint { x: i32, y: i32, save_blocks: bool },
+    Block { x: i32, y: i32 },
+    // a ball, which rolls rather than being carried
+    Ball { x: i32, y: i32 },
+    Door(Door),
+    // the wiring: inputs that turn a wire on, logic that combines wires, and things that a wire switches
+    Plate { wire: u32, x: i32, y: i32, players: bool },
+    Button { wire: u32, x: i32, y: i32, ms: u32 },
+    Timer { wire: u32, on: u32, off: u32 },
+    Zone { wire: u32, x: i32, y: i32, width: u32, height: u32 },
+    Logic { wire: u32, node: LogicNode },
+    Gate { wire: u32, x: i32, y: i32, length: i32, vertical: bool },
+    Light { wire: u32, x: i32, y: i32 },
+}
+
+// wire 0 is always on, so anything wired to it is always open (or moving, or lit)
+pub const ALWAYS_ON: u32 = 0;
+
+impl LevelObject {
+    // wire_out: the wire this object turns on, if it's an input or a bit of logic
+    pub fn wire_out(&self) -> Option<u32> {
+        match *self {
+            LevelObject::Plate { wire, .. } | LevelObject::Button { wire, .. } | LevelObject::Timer { wire, .. }
+            | LevelObject::Zone { wire, .. } | LevelObject::Logic { wire, .. } => Some(wire),
+            _ => None,
+        }
+    }
+
+    // wires_in: the wires this object listens to
+    pub fn wires_in(&self) -> Vec<u32> {
+        match self {
+            LevelObject::Logic { node, .. } => node.inputs(),
+            LevelObject::Gate { wire, .. } | LevelObject::Light { wire, .. } => vec!(*wire),
+            LevelObject::Platform(p) => vec!(p.wire),
+            LevelObject::Door(d) => vec!(d.wire),
+            _ => vec!(),
+        }
+    }
+}
+
+// writes an object back out as the line parse_level reads it from
+impl fmt::Display for LevelObject {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelObject::Size { width, height } => write!(f, "size-{}-{}", width, height),
+            LevelObject::Rules(rules) => {
+                write!(f, "rules")?;
+                rules.iter().try_for_each(|r| write!(f, "-{}", r.name()))
+            }
+            LevelObject::PortalBlock(s) => write!(f, "portalblock-{}-{}-{}-{}", s.x, s.y, s.width, s.height),
+            LevelObject::NonPortalBlock(s) => write!(f, "nonportalblock-{}-{}-{}-{}", s.x, s.y, s.width, s.height),
+            LevelObject::PortalGlass(s) => write!(f, "portalglass-{}-{}-{}-{}", s.x, s.y, s.width, s.height),
+            LevelObject::Grid(s) => write!(f, "grid-{}-{}-{}-{}", s.x, s.y, s.width, s.height),
+            LevelObject::Slope { surface: s, rises_right } =>
+                write!(f, "slope-{}-{}-{}-{}-{}", s.x, s.y, s.width, s.height, *rises_right as i32),
+            LevelObject::Hazard(h) =>
+                write!(f, "{}-{}-{}-{}-{}", h.kind.name(), h.surface.x, h.surface.y, h.surface.width, h.surface.height),
+            LevelObject::Platform(p) => {
+                let s = p.surface;
+                write!(f, "platform-{}-{}-{}-{}-{}-{}-{}", s.x, s.y, s.width, s.height, p.portal as i32, p.speed, p.wire)?;
+                for (x, y) in &p.waypoints {
+                    write!(f, "-{}-{}", x, y)?;
+                }
+                Ok(())
+            }
+            LevelObject::GatePlate { plate_x, plate_y, gate_x, gate_y, gate_length, gate_vertical } =>
+                write!(f, "gateplate-{}-{}-{}-{}-{}-{}", plate_x, plate_y, gate_x, gate_y, gate_length, *gate_vertical as i32),
+            LevelObject::Start { x, y } => write!(f, "start-{}-{}", x, y),
+            LevelObject::Checkpoint { x, y, save_blocks } => {
+                write!(f, "checkpoint-{}-{}", x, y)?;
+                if *save_blocks { write!(f, "-1")?; }
+                Ok(())
+            }
+            LevelObject::Block { x, y } => write!(f, "block-{}-{}", x, y),
+            LevelObject::Ball { x, y } => write!(f, "ball-{}-{}", x, y),
+            LevelObject::Door(d) => {
+                if d.wire != ALWAYS_ON {
+                    write!(f, "door-{}-{}-{}-{}-{}", d.x, d.y, d.width, d.height, d.wire)
+                } else if d.width == DOOR_WIDTH && d.height == DOOR_HEIGHT {
+                    write!(f, "door-{}-{}", d.x, d.y)
+                } else {
+                    write!(f, "door-{}-{}-{}-{}", d.x, d.y, d.width, d.height)
+                }
+            }
+            LevelObject::Plate { wire, x, y, players } => {
+                write!(f, "plate-{}-{}-{}", wire, x, y)?;
+                if *players { write!(f, "-1")?; }
+                Ok(())
+            }
+            LevelObject::Button { wire, x, y, ms } => write!(f, "button-{}-{}-{}-{}", wire, x, y, ms),
+            LevelObject::Timer { wire, on, off } => write!(f, "timer-{}-{}-{}", wire, on, off),
+            LevelObject::Zone { wire, x, y, width, height } => write!(f, "zone-{}-{}-{}-{}-{}", wire, x, y, width, height),
+            LevelObject::Logic { wire, node } => {
+                write!(f, "{}-{}", node.name(), wire)?;
+                match node {
+                    LogicNode::Delay { input, ms } => write!(f, "-{}-{}", input, ms),
+                    _ => node.inputs().iter().try_for_each(|w| write!(f, "-{}", w)),
+                }
+            }
+            LevelObject::Gate { wire, x, y, length, vertical } => write!(f, "gate-{}-{}-{}-{}-{}", wire, x, y, length, *vertical as i32),
+            LevelObject::Light { wire, x, y } => write!(f, "light-{}-{}-{}", wire, x, y),
+        }
+    }
+}
+
+// writes a whole level in the level file format, one object per line
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for obj in &self.objects {
+            writeln!(f, "{}", obj)?;
+        }
+        Ok(())
+    }
+}
+
+// Surface: a rectangle of tiles, positioned in pixels and sized in tiles
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Surface {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Surface {
+    pub fn collider(&self) -> RectCollider {
+        RectCollider::new(self.x as f32, self.y as f32, self.width as f32 * TILE_SIZE as f32, self.height as f32 * TILE_SIZE as f32)
+    }
+
+    // slope_collider: the triangle of a slope that fills this rectangle
+    pub fn slope_collider(&self, rises_right: bool) -> SlopeCollider {
+        SlopeCollider::new(self.x as f32, self.y as f32, self.width as f32 * TILE_SIZE as f32, self.height as f32 * TILE_SIZE as f32, rises_right)
+    }
+}
+
+// HazardKind: what a hazard is made of, which only changes how it looks
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum HazardKind {
+    Spikes,
+    Lava,
+    Acid,
+}
+
+impl HazardKind {
+    pub const ALL: [HazardKind; 3] = [HazardKind::Spikes, HazardKind::Lava, HazardKind::Acid];
+
+    // name: what it's called in a level file
+    pub fn name(&self) -> &'static str {
+        match self {
+            HazardKind::Spikes => "spikes",
+            HazardKind::Lava => "lava",
+            HazardKind::Acid => "acid",
+        }
+    }
+}
+
+// Rule: something a level doesn't let the player do
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Rule {
+    NoDash,
+    SingleJump,
+    WandOnly,
+    PotionsOnly,
+    BlueOnly,
+    OrangeOnly,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 6] = [Rule::NoDash, Rule::SingleJump, Rule::WandOnly, Rule::PotionsOnly, Rule::BlueOnly, Rule::OrangeOnly];
+
+    // name: what it's called in a level file
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rule::NoDash => "nodash",
+            Rule::SingleJump => "singlejump",
+            Rule::WandOnly => "wandonly",
+            Rule::PotionsOnly => "potionsonly",
+            Rule::BlueOnly => "blueonly",
+            Rule::OrangeOnly => "orangeonly",
+        }
+    }
+
+    // description: how the rule is shown to the player
+    pub fn description(&self) -> &'static str {
+        match self {
+            Rule::NoDash => "no dash",
+            Rule::SingleJump => "no double jump",
+            Rule::WandOnly => "no potions",
+            Rule::PotionsOnly => "no wand",
+            Rule::BlueOnly => "no orange portal",
+            Rule::OrangeOnly => "no blue portal",
+        }
+    }
+}
+
+// Hazard: a rectangle of tiles that kills the player and sends blocks back to where they started
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Hazard {
+    pub kind: HazardKind,
+    pub surface: Surface,
+}
+
+impl Hazard {
+    // collider: the tips of the spikes and the top of the liquid are drawn a little way down the tile,
+    // so only touching what's under them counts. a hazard with no height has nothing to touch
+    pub fn collider(&self) -> RectCollider {
+        let c = self.surface.collider();
+        RectCollider::new(c.x(), c.y() + 8.0, c.width(), (c.height() - 8.0).max(0.0))
+    }
+}
+
+// Platform: a surface that moves from where it's placed through each of its waypoints in turn, then back
+// to the start and round again. it only moves while its wire is on
+#[derive(Clone, Debug, PartialEq)]
+pub struct Platform {
+    pub surface: Surface,
+    pub portal: bool,
+    pub speed: u32,
+    pub wire: u32,
+    pub waypoints: Vec<(i32, i32)>,
+}
+
+// LogicNode: turns its wire on or off depending on the wires going into it
+#[derive(Clone, Debug, PartialEq)]
+pub enum LogicNode {
+    And(Vec<u32>),
+    Or(Vec<u32>),
+    Not(u32),
+    // turns on when its input does, and stays on for a number of milliseconds after its input turns off
+    Delay { input: u32, ms: u32 },
+}
+
+impl LogicNode {
+    // name: what it's called in a level file
+    pub fn name(&self) -> &'static str {
+        match self {
+            LogicNode::And(_) => "and",
+            LogicNode::Or(_) => "or",
+            LogicNode::Not(_) => "not",
+            LogicNode::Delay { .. } => "delay",
+        }
+    }
+
+    pub fn inputs(&self) -> Vec<u32> {
+        match self {
+            LogicNode::And(inputs) | LogicNode::Or(inputs) => inputs.clone(),
+            LogicNode::Not(input) | LogicNode::Delay { input, .. } => vec!(*input),
+        }
+    }
+}
+
+// Door: an exit from the level, positioned and sized in pixels. it's locked while its wire is off
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Door {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub wire: u32,
+}
+
+impl Door {
+    // the classic exit in the bottom right corner, for levels that don't place their own. levels too small for it
+    // (which the validator complains about) get it in the top left instead
+    pub fn default_exit(level_width: u32, level_height: u32) -> Door {
+        Door { x: level_width.saturating_sub(DOOR_WIDTH) as i32, y: level_height.saturating_sub(TILE_SIZE + DOOR_HEIGHT) as i32, width: DOOR_WIDTH, height: DOOR_HEIGHT, wire: ALWAYS_ON }
+    }
+
+    // collider: only the doorway itself counts, not the frame drawn around it
+    pub fn collider(&self) -> RectCollider {
+        let w = self.width as f32;
+        let h = self.height as f32;
+        RectCollider::new(self.x as f32 + w*25.0/160.0, self.y as f32 + h*89.0/230.0, w*70.0/160.0, h*140.0/230.0)
+    }
+}
+
+// LevelWatcher: notices when a level file has been saved, so it can be reloaded while the game is running
+pub struct LevelWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    last_poll: Instant,
+}
+
+impl LevelWatcher {
+    // how often to look at the file, checking every frame would hit the disk 60 times a second
+    const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+    pub fn new(filename: &str) -> LevelWatcher {
+        let path = resources::level_path(filename);
+        let modified = LevelWatcher::modified_time(&path);
+        LevelWatcher { path, modified, last_poll: Instant::now() }
+    }
+
+    // changed: whether the file has been modified since the last time this said so
+    pub fn changed(&mut self) -> bool {
+        if self.last_poll.elapsed() < LevelWatcher::POLL_INTERVAL {
+            return false;
+        }
+        self.last_poll = Instant::now();
+        let modified = LevelWatcher::modified_time(&self.path);
+        // a missing file is probably an editor halfway through saving it, so wait for it to come back
+        if modified.is_none() || modified == self.modified {
+            return false;
+        }
+        self.modified = modified;
+        true
+    }
+
+    fn modified_time(path: &Path) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+}
+
+// LevelPack: an ordered list of levels from packs.txt, played one after the other
+pub struct LevelPack {
+    pub name: String,
+    pub levels: Vec<PackEntry>,
+}
+
+// PackEntry: one level in a pack, with the name we show the player
+pub struct PackEntry {
+    pub file: String,
+    pub display_name: String,
+}
+
+// LevelError: why a level file or the pack manifest couldn't be loaded (or saved)
+#[derive(Debug)]
+pub enum LevelError {
+    Unreadable { file: String, reason: String },
+    Malformed { file: String, lines: Vec<LineError> },
+    UnknownPack { file: String, name: String },
+    BadMap { file: String, reason: String },
+}
+
+// LineError: a single malformed line, pointing at the field that is wrong
+#[derive(Debug)]
+pub struct LineError {
+    pub line: usize,
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl LineError {
+    fn new(line: usize, field: &'static str, message: String) -> LineError {
+        LineError { line, field, message }
+    }
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelError::Unreadable { file, reason } => write!(f, "{}: could not read or write file: {}", file, reason),
+            LevelError::UnknownPack { file, name } => write!(f, "{}: there is no level pack called \"{}\"", file, name),
+            LevelError::BadMap { file, reason } => write!(f, "{}: could not import Tiled map: {}", file, reason),
+            LevelError::Malformed { file, lines } => {
+                for (i, e) in lines.iter().enumerate() {
+                    if i > 0 { writeln!(f)?; }
+                    write!(f, "{}:{}: {}: {}", file, e.line, e.field, e.message)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+// the manifest that lists every level pack and the order of its levels
+pub const PACK_MANIFEST: &str = "packs.txt";
+
+// parse_level: reads a level file from the levels folder into a Level.
+// Tiled maps (.tmx, .tmj or .json) are imported on the fly
+pub(crate) fn parse_level(filename: &str) -> Result<Level, LevelError> {
+    if tiled::is_map_file(filename) {
+        return import_map(&resources::level_path(filename));
+    }
+    let path = resources::level_path(filename).display().to_string();
+    match fs::read_to_string(&path) {
+        Ok(contents) => parse_level_str(&path, &contents),
+        Err(e) => Err(LevelError::Unreadable { file: path, reason: e.to_string() }),
+    }
+}
+
+// import_map: reads a Tiled map from anywhere on disk
+pub(crate) fn import_map(path: &Path) -> Result<Level, LevelError> {
+    tiled::import(path).map_err(|reason| LevelError::BadMap { file: path.display().to_string(), reason })
+}
+
+// save_level: writes a level back out to a file in the levels folder
+pub(crate) fn save_level(filename: &str, level: &Level) -> Result<(), LevelError> {
+    let path = resources::level_path(filename);
+    // we only know how to read Tiled maps, so don't write a level file over one
+    if tiled::is_map_file(filename) {
+        return Err(LevelError::BadMap { file: path.display().to_string(), reason: "Tiled maps can't be saved over, import it into a .txt level instead".to_string() });
+    }
+    fs::write(&path, level.to_string()).map_err(|e| LevelError::Unreadable { file: path.display().to_string(), reason: e.to_string() })
+}
+
+// load_pack: finds a level pack by name in the manifest
+pub(crate) fn load_pack(name: &str) -> Result<LevelPack, LevelError> {
+    match load_all_packs()?.into_iter().find(|p| p.name == name) {
+        Some(pack) => Ok(pack),
+        None => Err(LevelError::UnknownPack { file: resources::level_path(PACK_MANIFEST).display().to_string(), name: name.to_string() }),
+    }
+}
+
+// load_all_packs: reads every pack in the manifest
+pub(crate) fn load_all_packs() -> Result<Vec<LevelPack>, LevelError> {
+    let path = resources::level_path(PACK_MANIFEST).display().to_string();
+    match fs::read_to_string(&path) {
+        Ok(contents) => parse_manifest_str(&path, &contents),
+        Err(e) => Err(LevelError::Unreadable { file: path, reason: e.to_string() }),
+    }
+}
+
+// parse_manifest_str: parses the pack manifest, where a pack-{name} line starts a pack
+// and each level-{file}-{display name} line after it adds a level to that pack
+pub(crate) fn parse_manifest_str(file: &str, contents: &str) -> Result<Vec<LevelPack>, LevelError> {
+    let mut packs: Vec<LevelPack> = vec![];
+    let mut pack_lines: Vec<usize> = vec![];
+    let mut errors: Vec<LineError> = vec![];
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() { continue; }
+        // display names are allowed to have dashes in them, so only split off the first two fields
+        let parts = line.splitn(3, '-').collect::<Vec<&str>>();
+        match parts[0] {
+            "pack" => match parts.get(1) {
+                Some(name) if !name.trim().is_empty() => {
+                    let name = parts[1..].join("-");
+                    packs.push(LevelPack { name: name.trim().to_string(), levels: vec![] });
+                    pack_lines.push(i + 1);
+                }
+                _ => errors.push(LineError::new(i + 1, "pack name", "missing".to_string())),
+            },
+            "level" => {
+                let file = match parts.get(1) {
+                    Some(file) if !file.trim().is_empty() => file.trim(),
+                    _ => { errors.push(LineError::new(i + 1, "level file", "missing".to_string())); continue; }
+                };
+                let display_name = parts.get(2).map_or(file, |n| n.trim());
+                match packs.last_mut() {
+                    Some(pack) => pack.levels.push(PackEntry { file: file.to_string(), display_name: display_name.to_string() }),
+                    None => errors.push(LineError::new(i + 1, "object type", "a level has to come after a pack-{name} line".to_string())),
+                }
+            }
+            other => errors.push(LineError::new(i + 1, "object type", format!("expected pack or level, found \"{}\"", other))),
+        }
+    }
+    for (pack, line) in packs.iter().zip(pack_lines) {
+        if pack.levels.is_empty() {
+            errors.push(LineError::new(line, "pack name", format!("pack \"{}\" has no levels", pack.name)));
+        }
+    }
+    if errors.is_empty() {
+        Ok(packs)
+    } else {
+        Err(LevelError::Malformed { file: file.to_string(), lines: errors })
+    }
+}
+
+// parse_level_str: parses the text of a level file, collecting an error for every malformed line
+pub(crate) fn parse_level_str(file: &str, contents: &str) -> Result<Level, LevelError> {
+    let mut objects: Vec<LevelObject> = vec![];
+    let mut errors: Vec<LineError> = vec![];
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() { continue; }
+        let parts = line.split('-').collect::<Vec<&str>>();
+        match parse_object(&parts, i + 1) {
+            Ok(obj) => objects.push(obj),
+            Err(e) => errors.push(e),
+        }
+    }
+    if errors.is_empty() {
+        Ok(Level { objects })
     } else {
-        "\n"
+        Err(LevelError::Malformed { file: file.to_string(), lines: errors })
+    }
+}
+
+fn parse_object(parts: &[&str], line: usize) -> Result<LevelObject, LineError> {
+    let mut fields = Fields { line, parts, next: 1 };
+    let obj = match parts[0] {
+        "size" => LevelObject::Size {
+            width: fields.count("width")?,
+            height: fields.count("height")?,
+        },
+        "rules" => {
+            let mut rules = vec!();
+            while fields.remaining() > 0 {
+                let name: String = fields.parse("rule", "a rule")?;
+                match Rule::ALL.iter().find(|r| r.name() == name) {
+                    Some(rule) => rules.push(*rule),
+                    None => {
+                        let names: Vec<&str> = Rule::ALL.iter().map(|r| r.name()).collect();
+                        return Err(LineError::new(line, "rule", format!("expected one of {}, found \"{}\"", names.join(", "), name)));
+                    }
+                }
+            }
+            LevelObject::Rules(rules)
+        }
+        "portalblock" => LevelObject::PortalBlock(fields.surface()?),
+        "nonportalblock" => LevelObject::NonPortalBlock(fields.surface()?),
+        "portalglass" => LevelObject::PortalGlass(fields.surface()?),
+        "grid" => LevelObject::Grid(fields.surface()?),
+        "spikes" | "lava" | "acid" => LevelObject::Hazard(Hazard {
+            kind: *HazardKind::ALL.iter().find(|k| k.name() == parts[0]).unwrap(),
+            surface: fields.surface()?,
+        }),
+        "slope" => LevelObject::Slope {
+            surface: fields.surface()?,
+            rises_right: fields.flag("direction")?,
+        },
+        "platform" => {
+            let surface = fields.surface()?;
+            let portal = fields.flag("portal")?;
+            let speed = fields.count("speed")?;
+            let wire = fields.count("wire")?;
+            // the rest of the line is the path, as many waypoints as it takes
+            let mut waypoints = vec!((fields.int("waypoint x position")?, fields.int("waypoint y position")?));
+            while fields.remaining() > 0 {
+                waypoints.push((fields.int("waypoint x position")?, fields.int("waypoint y position")?));
+            }
+            LevelObject::Platform(Platform { surface, portal, speed, wire, waypoints })
+        }
+        "gateplate" => LevelObject::GatePlate {
+            plate_x: fields.int("plate x position")?,
+            plate_y: fields.int("plate y position")?,
+            gate_x: fields.int("gate x position")?,
+            gate_y: fields.int("gate y position")?,
+            gate_length: fields.int("gate length")?,
+            gate_vertical: fields.flag("gate direction")?,
+        },
+        "start" => {
+            let start = LevelObject::Start { x: fields.int("x position")?, y: fields.int("y position")? };
+            // older levels packed the block's spawn point onto the start line
+            if fields.remaining() > 0 {
+                return Err(LineError::new(line, "block x position", "blocks now go on their own block-{x}-{y} line".to_string()));
+            }
+            start
+        }
+        "checkpoint" => {
+            let x = fields.int("x position")?;
+            let y = fields.int("y position")?;
+            // most checkpoints only move the respawn point
+            let save_blocks = fields.remaining() > 0 && fields.flag("save blocks")?;
+            LevelObject::Checkpoint { x, y, save_blocks }
+        }
+        "block" => LevelObject::Block {
+            x: fields.int("x position")?,
+            y: fields.int("y position")?,
+        },
+        "ball" => LevelObject::Ball {
+            x: fields.int("x position")?,
+            y: fields.int("y position")?,
+        },
+        "door" => {
+            let x = fields.int("x position")?;
+            let y = fields.int("y position")?;
+            // the size is optional, most doors are the size of the sprite
+            let (width, height) = if fields.remaining() > 0 {
+                (fields.count("width")?, fields.count("height")?)
+            } else {
+                (DOOR_WIDTH, DOOR_HEIGHT)
+            };
+            // and so is the wire that unlocks it
+            let wire = if fields.remaining() > 0 { fields.count("wire")? } else { ALWAYS_ON };
+            LevelObject::Door(Door { x, y, width, height, wire })
+        }
+        "plate" => {
+            let wire = fields.count("wire")?;
+            let x = fields.int("x position")?;
+            let y = fields.int("y position")?;
+            // most plates are only for blocks
+            let players = fields.remaining() > 0 && fields.flag("players")?;
+            LevelObject::Plate { wire, x, y, players }
+        }
+        "button" => LevelObject::Button {
+            wire: fields.count("wire")?,
+            x: fields.int("x position")?,
+            y: fields.int("y position")?,
+            ms: fields.count("milliseconds")?,
+        },
+        "timer" => LevelObject::Timer {
+            wire: fields.count("wire")?,
+            on: fields.count("milliseconds on")?,
+            off: fields.count("milliseconds off")?,
+        },
+        "zone" => LevelObject::Zone {
+            wire: fields.count("wire")?,
+            x: fields.int("x position")?,
+            y: fields.int("y position")?,
+            width: fields.count("width")?,
+            height: fields.count("height")?,
+        },
+        "and" | "or" => {
+            let wire = fields.count("wire")?;
+            let mut inputs = vec!(fields.count("input wire")?);
+            while fields.remaining() > 0 {
+                inputs.push(fields.count("input wire")?);
+            }
+            let node = if parts[0] == "and" { LogicNode::And(inputs) } else { LogicNode::Or(inputs) };
+            LevelObject::Logic { wire, node }
+        }
+        "not" => LevelObject::Logic {
+            wire: fields.count("wire")?,
+            node: LogicNode::Not(fields.count("input wire")?),
+        },
+        "delay" => LevelObject::Logic {
+            wire: fields.count("wire")?,
+            node: LogicNode::Delay { input: fields.count("input wire")?, ms: fields.count("milliseconds")? },
+        },
+        "gate" => LevelObject::Gate {
+            wire: fields.count("wire")?,
+            x: fields.int("x position")?,
+            y: fields.int("y position")?,
+            length: fields.int("length")?,
+            vertical: fields.flag("direction")?,
+        },
+        "light" => LevelObject::Light {
+            wire: fields.count("wire")?,
+            x: fields.int("x position")?,
+            y: fields.int("y position")?,
+        },
+        other => return Err(LineError::new(line, "object type", format!("unknown object type \"{}\"", other))),
     };
-    for a in fs::read_to_string("src/levels/".to_owned() + filename)
-        .unwrap()
-        .split(os_specific_linebreak)
-        .collect::<Vec<&str>>()
-    {
-        let result = a.split("-").collect::<Vec<&str>>();
-        let mut newresult: Vec<String> = vec![];
-        for r in result {
-            newresult.push(r.to_string());
-        }
-        results.push(newresult);
-    }
-    results
+    fields.finish()?;
+    Ok(obj)
+}
+
+// Fields: reads the dash-separated fields of one line in order, naming each one as it goes
+struct Fields<'a> {
+    line: usize,
+    parts: &'a [&'a str],
+    next: usize,
+}
+
+impl<'a> Fields<'a> {
+    fn parse<T: FromStr>(&mut self, name: &'static str, expected: &str) -> Result<T, LineError> {
+        let raw = match self.parts.get(self.next) {
+            Some(raw) => raw.trim(),
+            None => return Err(LineError::new(self.line, name, "missing".to_string())),
+        };
+        self.next += 1;
+        raw.parse().map_err(|_| LineError::new(self.line, name, format!("expected {}, found \"{}\"", expected, raw)))
+    }
+
+    fn int(&mut self, name: &'static str) -> Result<i32, LineError> {
+        self.parse(name, "a whole number")
+    }
+
+    fn count(&mut self, name: &'static str) -> Result<u32, LineError> {
+        self.parse(name, "a positive whole number")
+    }
+
+    fn flag(&mut self, name: &'static str) -> Result<bool, LineError> {
+        match self.int(name)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            other => Err(LineError::new(self.line, name, format!("expected 0 or 1, found \"{}\"", other))),
+        }
+    }
+
+    fn surface(&mut self) -> Result<Surface, LineError> {
+        Ok(Surface {
+            x: self.int("x position")?,
+            y: self.int("y position")?,
+            width: self.tiles("width in tiles")?,
+            height: self.tiles("height in tiles")?,
+        })
+    }
+
+    // tiles: a size in tiles, small enough that it's still a sensible number of pixels
+    fn tiles(&mut self, name: &'static str) -> Result<u32, LineError> {
+        let tiles = self.count(name)?;
+        if tiles > MAX_TILES {
+            return Err(LineError::new(self.line, name, format!("expected at most {} tiles, found {}", MAX_TILES, tiles)));
+        }
+        Ok(tiles)
+    }
+
+    fn remaining(&self) -> usize {
+        self.parts.len().saturating_sub(self.next)
+    }
+
+    // finish: makes sure there's nothing left over at the end of the line
+    fn finish(&self) -> Result<(), LineError> {
+        if self.remaining() > 0 {
+            return Err(LineError::new(self.line, "end of line", format!("unexpected extra field \"{}\"", self.parts[self.next])));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_exit_sits_in_the_bottom_right_corner() {
+        let door = Door::default_exit(WINDOW_WIDTH, WINDOW_HEIGHT);
+        assert_eq!((door.x, door.y), ((WINDOW_WIDTH - DOOR_WIDTH) as i32, (WINDOW_HEIGHT - TILE_SIZE - DOOR_HEIGHT) as i32));
+    }
+
+    #[test]
+    fn default_exit_fits_small_levels() {
+        let door = Door::default_exit(100, 200);
+        assert_eq!((door.x, door.y), (0, 0));
+    }
+
+    #[test]
+    fn hazards_only_hurt_below_their_top() {
+        let lava = Hazard { kind: HazardKind::Lava, surface: Surface { x: 0, y: 0, width: 1, height: 1 } };
+        assert_eq!((lava.collider().y(), lava.collider().height()), (8.0, 56.0));
+        let flat = Hazard { kind: HazardKind::Lava, surface: Surface { x: 0, y: 0, width: 1, height: 0 } };
+        assert_eq!(flat.collider().height(), 0.0);
+    }
+
+    fn parse_one(line: &str) -> LevelObject {
+        parse_level_str("test.txt", line).unwrap().objects.remove(0)
+    }
+
+    // parse_error: the one thing wrong with a line, as (field, message)
+    fn parse_error(line: &str) -> (&'static str, String) {
+        match parse_level_str("test.txt", line) {
+            Err(LevelError::Malformed { mut lines, .. }) if lines.len() == 1 => {
+                let e = lines.remove(0);
+                (e.field, e.message)
+            }
+            other => panic!("expected one malformed line, got {:?}", other.map(|l| l.to_string())),
+        }
+    }
+
+    fn surface(x: i32, y: i32, width: u32, height: u32) -> Surface {
+        Surface { x, y, width, height }
+    }
+
+    #[test]
+    fn parses_every_kind_of_object() {
+        let cases = vec!(
+            ("size-2560-1440", LevelObject::Size { width: 2560, height: 1440 }),
+            ("rules-nodash-wandonly", LevelObject::Rules(vec!(Rule::NoDash, Rule::WandOnly))),
+            ("portalblock-0-656-20-1", LevelObject::PortalBlock(surface(0, 656, 20, 1))),
+            ("nonportalblock-64-0-2-3", LevelObject::NonPortalBlock(surface(64, 0, 2, 3))),
+            ("portalglass-128-64-1-4", LevelObject::PortalGlass(surface(128, 64, 1, 4))),
+            ("grid-300-64-1-3", LevelObject::Grid(surface(300, 64, 1, 3))),
+            ("slope-0-592-2-1-1", LevelObject::Slope { surface: surface(0, 592, 2, 1), rises_right: true }),
+            ("spikes-0-0-1-1", LevelObject::Hazard(Hazard { kind: HazardKind::Spikes, surface: surface(0, 0, 1, 1) })),
+            ("lava-64-0-2-1", LevelObject::Hazard(Hazard { kind: HazardKind::Lava, surface: surface(64, 0, 2, 1) })),
+            ("acid-128-0-3-1", LevelObject::Hazard(Hazard { kind: HazardKind::Acid, surface: surface(128, 0, 3, 1) })),
+            ("platform-100-200-2-1-1-3-4-400-200-400-500", LevelObject::Platform(Platform {
+                surface: surface(100, 200, 2, 1), portal: true, speed: 3, wire: 4, waypoints: vec!((400, 200), (400, 500)),
+            })),
+            ("gateplate-300-592-600-64-592-1", LevelObject::GatePlate { plate_x: 300, plate_y: 592, gate_x: 600, gate_y: 64, gate_length: 592, gate_vertical: true }),
+            ("start-100-558", LevelObject::Start { x: 100, y: 558 }),
+            ("checkpoint-500-528", LevelObject::Checkpoint { x: 500, y: 528, save_blocks: false }),
+            ("checkpoint-500-528-1", LevelObject::Checkpoint { x: 500, y: 528, save_blocks: true }),
+            ("block-300-100", LevelObject::Block { x: 300, y: 100 }),
+            ("ball-400-100", LevelObject::Ball { x: 400, y: 100 }),
+            ("door-1120-426", LevelObject::Door(Door { x: 1120, y: 426, width: DOOR_WIDTH, height: DOOR_HEIGHT, wire: ALWAYS_ON })),
+            ("door-1120-426-80-115", LevelObject::Door(Door { x: 1120, y: 426, width: 80, height: 115, wire: ALWAYS_ON })),
+            ("door-1120-426-160-230-2", LevelObject::Door(Door { x: 1120, y: 426, width: 160, height: 230, wire: 2 })),
+            ("plate-1-300-640", LevelObject::Plate { wire: 1, x: 300, y: 640, players: false }),
+            ("plate-1-300-640-1", LevelObject::Plate { wire: 1, x: 300, y: 640, players: true }),
+            ("button-2-300-500-1000", LevelObject::Button { wire: 2, x: 300, y: 500, ms: 1000 }),
+            ("timer-3-500-1500", LevelObject::Timer { wire: 3, on: 500, off: 1500 }),
+            ("zone-4-0-0-100-200", LevelObject::Zone { wire: 4, x: 0, y: 0, width: 100, height: 200 }),
+            ("and-5-1-2-3", LevelObject::Logic { wire: 5, node: LogicNode::And(vec!(1, 2, 3)) }),
+            ("or-6-1-2", LevelObject::Logic { wire: 6, node: LogicNode::Or(vec!(1, 2)) }),
+            ("not-7-1", LevelObject::Logic { wire: 7, node: LogicNode::Not(1) }),
+            ("delay-8-1-250", LevelObject::Logic { wire: 8, node: LogicNode::Delay { input: 1, ms: 250 } }),
+            ("gate-1-600-64-592-1", LevelObject::Gate { wire: 1, x: 600, y: 64, length: 592, vertical: true }),
+            ("light-1-700-100", LevelObject::Light { wire: 1, x: 700, y: 100 }),
+        );
+        for (line, expected) in cases {
+            let obj = parse_one(line);
+            assert_eq!(obj, expected, "{}", line);
+            // and it writes back out the same way
+            assert_eq!(obj.to_string(), line);
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_objects() {
+        assert_eq!(parse_error("teleporter-1-2"), ("object type", "unknown object type \"teleporter\"".to_string()));
+        assert_eq!(parse_error("rules-nodash-fly").0, "rule");
+    }
+
+    #[test]
+    fn rejects_bad_numbers() {
+        assert_eq!(parse_error("block-10-abc"), ("y position", "expected a whole number, found \"abc\"".to_string()));
+        assert_eq!(parse_error("timer-1-500"), ("milliseconds off", "missing".to_string()));
+        assert_eq!(parse_error("slope-0-0-1-1-2"), ("direction", "expected 0 or 1, found \"2\"".to_string()));
+        assert_eq!(parse_error("block-10-20-30"), ("end of line", "unexpected extra field \"30\"".to_string()));
+        assert_eq!(parse_error("portalblock-0-0-99999999-1").0, "width in tiles");
+    }
+
+    #[test]
+    fn points_old_start_lines_at_the_block_line() {
+        assert_eq!(parse_error("start-100-558-300-100"), ("block x position", "blocks now go on their own block-{x}-{y} line".to_string()));
+    }
+
+    #[test]
+    fn reports_every_bad_line() {
+        match parse_level_str("test.txt", "start-100-558\nblock-x-1\n\nwall-1\n") {
+            Err(LevelError::Malformed { lines, .. }) => assert_eq!(lines.iter().map(|e| e.line).collect::<Vec<usize>>(), vec!(2, 4)),
+            _ => panic!("expected the level to be malformed"),
+        }
+    }
+
+    #[test]
+    fn shipped_levels_survive_a_round_trip() {
+        let shipped = [
+            include_str!("level0.txt"),
+            include_str!("level1.txt"),
+            include_str!("level2.txt"),
+            include_str!("level3.txt"),
+            include_str!("level4.txt"),
+        ];
+        for contents in shipped.iter() {
+            let level = parse_level_str("test.txt", contents).unwrap();
+            let again = parse_level_str("test.txt", &level.to_string()).unwrap();
+            assert_eq!(level.objects, again.objects);
+        }
+    }
 }
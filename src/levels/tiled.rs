@@ -0,0 +1,511 @@
+// Tiled: imports maps made in the Tiled editor (https://www.mapeditor.org), either .tmx (XML) or .tmj/.json
+//
+// tile layers become surfaces. each tile in the tileset says what it is with a "surface" property
+// (portal, nonportal, glass or grid), and neighbouring tiles of the same kind are merged into one rectangle.
+// tiles without the property are just decoration and are left out.
+//
+// objects become everything else, picked by their class (called type before Tiled 1.9):
+//   start     - where the player spawns (the top left of the object)
+//   block     - where a block spawns
+//   ball      - where a ball spawns
+//   door      - an exit, sized like the object (or the sprite, if the object has no size)
+//   gate      - a gate, vertical if the object is taller than it is wide. only placed along with a plate
+//   plate     - a pressure plate. it needs an object property called "gate" pointing at the gate it opens
+//   slope     - a slope filling the object, rising to the right unless it has a "rises" property set to left
+//   spikes    - a hazard filling the object, rounded to whole tiles
+//   lava      - the same, as lava
+//   acid      - the same, as acid
+// objects with any other class are notes or decoration for whoever made the map, and are left out.
+//
+// maps bigger than the window become levels that scroll.
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+
+use crate::levels::{Door, Hazard, HazardKind, Level, LevelObject, Surface, ALWAYS_ON, DOOR_HEIGHT, DOOR_WIDTH, MAX_TILES, TILE_SIZE, WINDOW_HEIGHT, WINDOW_WIDTH};
+
+// the top bits of a tile id say whether it's flipped, which doesn't matter to us
+const FLIP_FLAGS: u32 = 0xF000_0000;
+
+#[derive(Copy, Clone, PartialEq)]
+enum Kind {
+    Portal,
+    NonPortal,
+    Glass,
+    Grid,
+}
+
+// Map: the parts of a Tiled map we care about, read out of either format
+struct Map {
+    width: usize,
+    height: usize,
+    // how much to scale Tiled's pixels by to get ours, for maps that don't use 64 px tiles
+    scale: f32,
+    layers: Vec<Vec<u32>>,
+    // tile kinds by global tile id
+    kinds: HashMap<u32, Kind>,
+    objects: Vec<MapObject>,
+}
+
+struct MapObject {
+    id: u32,
+    class: String,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    // tile objects are positioned by their bottom left corner instead of their top left
+    is_tile: bool,
+    properties: HashMap<String, String>,
+}
+
+// is_map_file: whether a level file is really a Tiled map that should be imported instead of parsed
+pub(crate) fn is_map_file(filename: &str) -> bool {
+    let lower = filename.to_lowercase();
+    lower.ends_with(".tmx") || lower.ends_with(".tmj") || lower.ends_with(".json")
+}
+
+// import: reads a Tiled map into a Level
+pub(crate) fn import(path: &Path) -> Result<Level, String> {
+    let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let map = if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("tmx")) {
+        read_tmx(&contents, dir)?
+    } else {
+        read_json(&contents, dir)?
+    };
+    convert(&map)
+}
+
+fn convert(map: &Map) -> Result<Level, String> {
+    let mut objects: Vec<LevelObject> = vec!();
+    if map.width > MAX_TILES as usize || map.height > MAX_TILES as usize {
+        return Err(format!("the map is {}x{} tiles, but a level can only be {} tiles across or down", map.width, map.height, MAX_TILES));
+    }
+    let (width, height) = (map.width as u32 * TILE_SIZE, map.height as u32 * TILE_SIZE);
+    if width > WINDOW_WIDTH || height > WINDOW_HEIGHT {
+        objects.push(LevelObject::Size { width: width.max(WINDOW_WIDTH), height: height.max(WINDOW_HEIGHT) });
+    }
+    for layer in &map.layers {
+        if layer.len() != map.width * map.height {
+            return Err(format!("a tile layer has {} tiles but the map is {}x{}", layer.len(), map.width, map.height));
+        }
+        objects.extend(merge_tiles(map, layer));
+    }
+
+    let gates: HashMap<u32, &MapObject> = map.objects.iter().filter(|o| o.class == "gate").map(|o| (o.id, o)).collect();
+    for obj in &map.objects {
+        let (x, y) = map.top_left(obj);
+        match obj.class.as_str() {
+            "start" => objects.push(LevelObject::Start { x, y }),
+            "block" => objects.push(LevelObject::Block { x, y }),
+            "ball" => objects.push(LevelObject::Ball { x, y }),
+            "door" => {
+                let (width, height) = if obj.width > 0.0 && obj.height > 0.0 {
+                    ((obj.width * map.scale).round() as u32, (obj.height * map.scale).round() as u32)
+                } else {
+                    (DOOR_WIDTH, DOOR_HEIGHT)
+                };
+                objects.push(LevelObject::Door(Door { x, y, width, height, wire: ALWAYS_ON }));
+            }
+            "plate" => {
+                let gate = obj.properties.get("gate")
+                    .and_then(|id| id.parse::<u32>().ok())
+                    .and_then(|id| gates.get(&id))
+                    .ok_or_else(|| format!("plate {} needs a \"gate\" property pointing at a gate object", obj.id))?;
+                let (gate_x, gate_y) = map.top_left(gate);
+                let gate_vertical = gate.height > gate.width;
+                let gate_length = (gate.width.max(gate.height) * map.scale).round() as i32;
+                objects.push(LevelObject::GatePlate { plate_x: x, plate_y: y, gate_x, gate_y, gate_length, gate_vertical });
+            }
+            "slope" => {
+                let surface = Surface { x, y, width: map.tiles(obj.width), height: map.tiles(obj.height) };
+                let rises_right = obj.properties.get("rises").is_none_or(|r| r != "left");
+                objects.push(LevelObject::Slope { surface, rises_right });
+            }
+            "spikes" | "lava" | "acid" => {
+                let kind = *HazardKind::ALL.iter().find(|k| k.name() == obj.class).unwrap();
+                let surface = Surface { x, y, width: map.tiles(obj.width), height: map.tiles(obj.height) };
+                objects.push(LevelObject::Hazard(Hazard { kind, surface }));
+            }
+            // gates are added along with the plate that opens them
+            "gate" => {}
+            // anything else is a note or decoration for whoever made the map
+            _ => {}
+        }
+    }
+    Ok(Level { objects })
+}
+
+impl Map {
+    fn top_left(&self, obj: &MapObject) -> (i32, i32) {
+        let y = if obj.is_tile { obj.y - obj.height } else { obj.y };
+        ((obj.x * self.scale).round() as i32, (y * self.scale).round() as i32)
+    }
+
+    // tiles: how many whole tiles a size in map pixels covers, at least one
+    fn tiles(&self, size: f32) -> u32 {
+        ((size * self.scale) / TILE_SIZE as f32).round().max(1.0) as u32
+    }
+}
+
+// merge_tiles: turns a layer of tiles into as few surfaces as it can, growing each one
+// right as far as it goes and then down as far as the whole row matches
+fn merge_tiles(map: &Map, layer: &[u32]) -> Vec<LevelObject> {
+    let kind_at = |col: usize, row: usize| map.kinds.get(&(layer[row * map.width + col] & !FLIP_FLAGS)).copied();
+    let mut used = vec![false; layer.len()];
+    let mut surfaces: Vec<LevelObject> = vec!();
+    for row in 0..map.height {
+        for col in 0..map.width {
+            let kind = match kind_at(col, row) {
+                Some(kind) if !used[row * map.width + col] => kind,
+                _ => continue,
+            };
+            let mut width = 1;
+            while col + width < map.width && !used[row * map.width + col + width] && kind_at(col + width, row) == Some(kind) {
+                width += 1;
+            }
+            let mut height = 1;
+            while row + height < map.height
+                && (col..col + width).all(|c| !used[(row + height) * map.width + c] && kind_at(c, row + height) == Some(kind)) {
+                height += 1;
+            }
+            for r in row..row + height {
+                for c in col..col + width {
+                    used[r * map.width + c] = true;
+                }
+            }
+            let surface = Surface { x: (col as u32 * TILE_SIZE) as i32, y: (row as u32 * TILE_SIZE) as i32, width: width as u32, height: height as u32 };
+            surfaces.push(match kind {
+                Kind::Portal => LevelObject::PortalBlock(surface),
+                Kind::NonPortal => LevelObject::NonPortalBlock(surface),
+                Kind::Glass => LevelObject::PortalGlass(surface),
+                Kind::Grid => LevelObject::Grid(surface),
+            });
+        }
+    }
+    surfaces
+}
+
+fn parse_kind(value: &str) -> Option<Kind> {
+    match value.trim().to_lowercase().as_str() {
+        "portal" | "portalblock" => Some(Kind::Portal),
+        "nonportal" | "nonportalblock" => Some(Kind::NonPortal),
+        "glass" | "portalglass" => Some(Kind::Glass),
+        "grid" => Some(Kind::Grid),
+        _ => None,
+    }
+}
+
+/* TMX */
+
+fn read_tmx(contents: &str, dir: &Path) -> Result<Map, String> {
+    let doc = roxmltree::Document::parse(contents).map_err(|e| e.to_string())?;
+    let root = doc.root_element();
+    if root.attribute("infinite") == Some("1") {
+        return Err("infinite maps aren't supported, turn off \"Infinite\" in the map properties".to_string());
+    }
+    let mut map = Map {
+        width: number(root.attribute("width"), "map width")?,
+        height: number(root.attribute("height"), "map height")?,
+        scale: TILE_SIZE as f32 / number::<f32>(root.attribute("tilewidth"), "tile width")?,
+        layers: vec!(),
+        kinds: HashMap::new(),
+        objects: vec!(),
+    };
+
+    for tileset in root.children().filter(|n| n.has_tag_name("tileset")) {
+        let first_gid: u32 = number(tileset.attribute("firstgid"), "tileset firstgid")?;
+        // tilesets can be saved in their own .tsx file
+        match tileset.attribute("source") {
+            Some(source) => {
+                let path = dir.join(source);
+                let text = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+                let external = roxmltree::Document::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+                read_tmx_tiles(external.root_element(), first_gid, &mut map.kinds)?;
+            }
+            None => read_tmx_tiles(tileset, first_gid, &mut map.kinds)?,
+        }
+    }
+
+    // layers can be nested in groups, so look at every descendant
+    for layer in root.descendants().filter(|n| n.has_tag_name("layer")) {
+        let data = match layer.children().find(|n| n.has_tag_name("data")) {
+            Some(data) => data,
+            None => continue,
+        };
+        let tiles = match data.attribute("encoding") {
+            Some("csv") => data.text().unwrap_or("")
+                .split(',')
+                .map(|t| number(Some(t.trim()), "tile id"))
+                .collect::<Result<Vec<u32>, String>>()?,
+            None => data.children()
+                .filter(|n| n.has_tag_name("tile"))
+                .map(|t| number(Some(t.attribute("gid").unwrap_or("0")), "tile id"))
+                .collect::<Result<Vec<u32>, String>>()?,
+            Some(other) => return Err(format!("tile layers saved as {} aren't supported, set the layer format to CSV", other)),
+        };
+        map.layers.push(tiles);
+    }
+
+    for object in root.descendants().filter(|n| n.has_tag_name("objectgroup")).flat_map(|g| g.children()).filter(|n| n.has_tag_name("object")) {
+        let mut properties = HashMap::new();
+        for prop in object.children().filter(|n| n.has_tag_name("properties")).flat_map(|p| p.children()).filter(|n| n.has_tag_name("property")) {
+            if let (Some(name), Some(value)) = (prop.attribute("name"), prop.attribute("value")) {
+                properties.insert(name.to_string(), value.to_string());
+            }
+        }
+        map.objects.push(MapObject {
+            id: number(object.attribute("id"), "object id")?,
+            class: object.attribute("class").or_else(|| object.attribute("type")).unwrap_or("").to_lowercase(),
+            x: number(object.attribute("x"), "object x")?,
+            y: number(object.attribute("y"), "object y")?,
+            width: number(Some(object.attribute("width").unwrap_or("0")), "object width")?,
+            height: number(Some(object.attribute("height").unwrap_or("0")), "object height")?,
+            is_tile: object.attribute("gid").is_some(),
+            properties,
+        });
+    }
+    Ok(map)
+}
+
+fn read_tmx_tiles(tileset: roxmltree::Node, first_gid: u32, kinds: &mut HashMap<u32, Kind>) -> Result<(), String> {
+    for tile in tileset.children().filter(|n| n.has_tag_name("tile")) {
+        let id: u32 = number(tile.attribute("id"), "tile id")?;
+        let property = tile.children()
+            .filter(|n| n.has_tag_name("properties"))
+            .flat_map(|p| p.children())
+            .find(|n| n.has_tag_name("property") && n.attribute("name") == Some("surface"))
+            .and_then(|p| p.attribute("value"));
+        // a tile's class works as well as a property
+        let kind = property.or_else(|| tile.attribute("class")).or_else(|| tile.attribute("type")).and_then(parse_kind);
+        if let Some(kind) = kind {
+            kinds.insert(gid(first_gid, id)?, kind);
+        }
+    }
+    Ok(())
+}
+
+// gid: the global id of a tile in a tileset, which is only as big as a tile layer can hold
+fn gid(first_gid: u32, id: u32) -> Result<u32, String> {
+    first_gid.checked_add(id)
+        .filter(|gid| gid & FLIP_FLAGS == 0)
+        .ok_or_else(|| format!("tile {} of the tileset starting at {} has too big an id", id, first_gid))
+}
+
+fn number<T: std::str::FromStr>(value: Option<&str>, name: &str) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("{} is missing", name))?;
+    value.parse().map_err(|_| format!("{} should be a number, found \"{}\"", name, value))
+}
+
+/* JSON */
+
+fn read_json(contents: &str, dir: &Path) -> Result<Map, String> {
+    let root: Value = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+    if root["infinite"].as_bool() == Some(true) {
+        return Err("infinite maps aren't supported, turn off \"Infinite\" in the map properties".to_string());
+    }
+    let mut map = Map {
+        width: json_number(&root["width"], "map width")? as usize,
+        height: json_number(&root["height"], "map height")? as usize,
+        scale: TILE_SIZE as f32 / json_number(&root["tilewidth"], "tile width")? as f32,
+        layers: vec!(),
+        kinds: HashMap::new(),
+        objects: vec!(),
+    };
+
+    for tileset in root["tilesets"].as_array().into_iter().flatten() {
+        let first_gid = json_number(&tileset["firstgid"], "tileset firstgid")? as u32;
+        match tileset["source"].as_str() {
+            Some(source) => {
+                let path = dir.join(source);
+                let text = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+                let external: Value = serde_json::from_str(&text)
+                    .map_err(|e| format!("{}: {} (only .tsj/.json tilesets work with .tmj maps)", path.display(), e))?;
+                read_json_tiles(&external, first_gid, &mut map.kinds)?;
+            }
+            None => read_json_tiles(tileset, first_gid, &mut map.kinds)?,
+        }
+    }
+
+    read_json_layers(&root["layers"], &mut map)?;
+    Ok(map)
+}
+
+// read_json_layers: reads tile and object layers, going into groups
+fn read_json_layers(layers: &Value, map: &mut Map) -> Result<(), String> {
+    for layer in layers.as_array().into_iter().flatten() {
+        match layer["type"].as_str() {
+            Some("tilelayer") => {
+                let data = layer["data"].as_array()
+                    .ok_or("tile layers saved as base64 aren't supported, set the layer format to CSV")?;
+                let tiles = data.iter()
+                    .map(|t| json_number(t, "tile id").map(|t| t as u32))
+                    .collect::<Result<Vec<u32>, String>>()?;
+                map.layers.push(tiles);
+            }
+            Some("objectgroup") => {
+                for object in layer["objects"].as_array().into_iter().flatten() {
+                    let mut properties = HashMap::new();
+                    for prop in object["properties"].as_array().into_iter().flatten() {
+                        if let Some(name) = prop["name"].as_str() {
+                            let value = match &prop["value"] {
+                                Value::String(s) => s.clone(),
+                                other => other.to_string(),
+                            };
+                            properties.insert(name.to_string(), value);
+                        }
+                    }
+                    let class = object["class"].as_str().filter(|c| !c.is_empty()).or_else(|| object["type"].as_str()).unwrap_or("");
+                    map.objects.push(MapObject {
+                        id: json_number(&object["id"], "object id")? as u32,
+                        class: class.to_lowercase(),
+                        x: json_number(&object["x"], "object x")? as f32,
+                        y: json_number(&object["y"], "object y")? as f32,
+                        width: object["width"].as_f64().unwrap_or(0.0) as f32,
+                        height: object["height"].as_f64().unwrap_or(0.0) as f32,
+                        is_tile: object.get("gid").is_some(),
+                        properties,
+                    });
+                }
+            }
+            Some("group") => read_json_layers(&layer["layers"], map)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn read_json_tiles(tileset: &Value, first_gid: u32, kinds: &mut HashMap<u32, Kind>) -> Result<(), String> {
+    for tile in tileset["tiles"].as_array().into_iter().flatten() {
+        let id = json_number(&tile["id"], "tile id")? as u32;
+        let property = tile["properties"].as_array().into_iter().flatten()
+            .find(|p| p["name"].as_str() == Some("surface"))
+            .and_then(|p| p["value"].as_str());
+        let kind = property.or_else(|| tile["class"].as_str()).or_else(|| tile["type"].as_str()).and_then(parse_kind);
+        if let Some(kind) = kind {
+            kinds.insert(gid(first_gid, id)?, kind);
+        }
+    }
+    Ok(())
+}
+
+fn json_number(value: &Value, name: &str) -> Result<f64, String> {
+    value.as_f64().ok_or_else(|| format!("{} should be a number, found {}", name, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a 4x3 map of 32 px tiles (so everything doubles in size): a 2x2 portal block in the top left, and nonportal
+    // tiles along the bottom and up the right. one of the bottom tiles is flipped
+    const TMX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.10" orientation="orthogonal" width="4" height="3" tilewidth="32" tileheight="32" infinite="0">
+ <tileset firstgid="1" name="tiles" tilewidth="32" tileheight="32" tilecount="2" columns="2">
+  <tile id="0"><properties><property name="surface" value="portal"/></properties></tile>
+  <tile id="1" class="nonportal"/>
+ </tileset>
+ <layer id="1" name="walls" width="4" height="3">
+  <data encoding="csv">1,1,0,0,1,1,0,2,2,2147483650,2,2</data>
+ </layer>
+ <objectgroup id="2" name="things">
+  <object id="1" type="start" x="10" y="20"/>
+  <object id="2" type="gate" x="50" y="0" width="16" height="64"/>
+  <object id="3" type="plate" x="20" y="60"><properties><property name="gate" type="object" value="2"/></properties></object>
+  <object id="4" type="lava" x="0" y="64" width="64" height="32"/>
+  <object id="5" class="door" x="100" y="10" width="40" height="50"/>
+  <object id="6" type="note" x="0" y="0"/>
+ </objectgroup>
+</map>"#;
+
+    const JSON: &str = r#"{
+ "width": 4, "height": 3, "tilewidth": 32, "tileheight": 32, "infinite": false,
+ "tilesets": [{ "firstgid": 1, "name": "tiles", "tiles": [
+  { "id": 0, "properties": [{ "name": "surface", "type": "string", "value": "portal" }] },
+  { "id": 1, "type": "nonportal" }
+ ]}],
+ "layers": [
+  { "type": "tilelayer", "name": "walls", "width": 4, "height": 3, "data": [1, 1, 0, 0, 1, 1, 0, 2, 2, 2147483650, 2, 2] },
+  { "type": "group", "name": "nested", "layers": [{ "type": "objectgroup", "name": "things", "objects": [
+   { "id": 1, "type": "start", "x": 10, "y": 20 },
+   { "id": 2, "type": "gate", "x": 50, "y": 0, "width": 16, "height": 64 },
+   { "id": 3, "type": "plate", "x": 20, "y": 60, "properties": [{ "name": "gate", "type": "object", "value": 2 }] },
+   { "id": 4, "type": "lava", "x": 0, "y": 64, "width": 64, "height": 32 },
+   { "id": 5, "class": "door", "x": 100, "y": 10, "width": 40, "height": 50 },
+   { "id": 6, "type": "note", "x": 0, "y": 0 }
+  ]}]}
+ ]
+}"#;
+
+    fn surface(x: i32, y: i32, width: u32, height: u32) -> Surface {
+        Surface { x, y, width, height }
+    }
+
+    // what both fixtures should turn into
+    fn expected() -> Vec<LevelObject> {
+        vec!(
+            LevelObject::PortalBlock(surface(0, 0, 2, 2)),
+            LevelObject::NonPortalBlock(surface(192, 64, 1, 2)),
+            LevelObject::NonPortalBlock(surface(0, 128, 3, 1)),
+            LevelObject::Start { x: 20, y: 40 },
+            LevelObject::GatePlate { plate_x: 40, plate_y: 120, gate_x: 100, gate_y: 0, gate_length: 128, gate_vertical: true },
+            LevelObject::Hazard(Hazard { kind: HazardKind::Lava, surface: surface(0, 128, 2, 1) }),
+            LevelObject::Door(Door { x: 200, y: 20, width: 80, height: 100, wire: ALWAYS_ON }),
+        )
+    }
+
+    #[test]
+    fn imports_tmx() {
+        let level = convert(&read_tmx(TMX, Path::new(".")).unwrap()).unwrap();
+        assert_eq!(level.objects, expected());
+    }
+
+    #[test]
+    fn imports_json() {
+        let level = convert(&read_json(JSON, Path::new(".")).unwrap()).unwrap();
+        assert_eq!(level.objects, expected());
+    }
+
+    #[test]
+    fn merges_tiles_into_rectangles() {
+        let map = Map {
+            width: 5,
+            height: 3,
+            scale: 1.0,
+            layers: vec!(),
+            kinds: vec!((1, Kind::Portal), (2, Kind::Glass)).into_iter().collect(),
+            objects: vec!(),
+        };
+        // a 3x2 block with a notch out of its bottom right, and glass next to it that isn't merged in
+        let layer = vec!(
+            1, 1, 1, 2, 0,
+            1, 1, 0, 2, 0,
+            0, 0, 0, 0, 1 | 0x8000_0000,
+        );
+        assert_eq!(merge_tiles(&map, &layer), vec!(
+            LevelObject::PortalBlock(surface(0, 0, 3, 1)),
+            LevelObject::PortalGlass(surface(192, 0, 1, 2)),
+            LevelObject::PortalBlock(surface(0, 64, 2, 1)),
+            LevelObject::PortalBlock(surface(256, 128, 1, 1)),
+        ));
+    }
+
+    #[test]
+    fn rejects_maps_too_big_for_a_level() {
+        let tmx = format!(r#"<map width="{}" height="1" tilewidth="64" tileheight="64"></map>"#, MAX_TILES + 1);
+        assert!(convert(&read_tmx(&tmx, Path::new(".")).unwrap()).is_err());
+    }
+
+    #[test]
+    fn rejects_tile_ids_too_big_for_a_layer() {
+        let tmx = r#"<map width="1" height="1" tilewidth="64" tileheight="64">
+ <tileset firstgid="268435455" name="tiles"><tile id="1" class="portal"/></tileset>
+</map>"#;
+        assert!(read_tmx(tmx, Path::new(".")).is_err());
+        let json = r#"{ "width": 1, "height": 1, "tilewidth": 64, "tilesets": [{ "firstgid": 4294967295, "tiles": [{ "id": 1, "type": "portal" }] }] }"#;
+        assert!(read_json(json, Path::new(".")).is_err());
+    }
+}
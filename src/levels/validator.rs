@@ -0,0 +1,380 @@
+// Validator: checks level files for mistakes without opening a window, so CI can catch broken levels
+use std::collections::HashSet;
+
+use crate::levels::{self, Hazard, Level, LevelObject, Platform, Rule, ALWAYS_ON, TILE_SIZE, WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::rect_collider::RectCollider;
+use crate::slope_collider::SlopeCollider;
+
+// the player's collider at the start point
+const PLAYER_WIDTH: f32 = 69.0;
+const PLAYER_HEIGHT: f32 = 98.0;
+const BLOCK_SIZE: f32 = (TILE_SIZE/2) as f32;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+// Problem: something wrong with one object in a level
+pub struct Problem {
+    pub severity: Severity,
+    pub object: String,
+    pub message: String,
+}
+
+impl Problem {
+    fn error(obj: &LevelObject, message: String) -> Problem {
+        Problem { severity: Severity::Error, object: obj.to_string(), message }
+    }
+
+    fn warning(obj: &LevelObject, message: String) -> Problem {
+        Problem { severity: Severity::Warning, object: obj.to_string(), message }
+    }
+}
+
+// run: validates the given level files, or every level in packs.txt if none are given.
+// prints a report and returns the exit code (1 if any level has errors)
+pub(crate) fn run(files: &[String]) -> i32 {
+    let mut errors = 0;
+    let mut warnings = 0;
+    let mut to_check: Vec<String> = files.to_vec();
+    if to_check.is_empty() {
+        match levels::load_all_packs() {
+            Ok(packs) => {
+                let mut seen = HashSet::new();
+                for entry in packs.iter().flat_map(|p| p.levels.iter()) {
+                    if seen.insert(entry.file.clone()) {
+                        to_check.push(entry.file.clone());
+                    }
+                }
+            }
+            Err(e) => {
+                println!("{}", e);
+                return 1;
+            }
+        }
+    }
+    for file in &to_check {
+        let level = match levels::parse_level(file) {
+            Ok(level) => level,
+            Err(e) => {
+                println!("{}", e);
+                errors += 1;
+                continue;
+            }
+        };
+        for problem in validate(&level) {
+            let label = match problem.severity {
+                Severity::Error => { errors += 1; "error" }
+                Severity::Warning => { warnings += 1; "warning" }
+            };
+            println!("{}: {}: {}: {}", file, label, problem.object, problem.message);
+        }
+    }
+    println!("checked {} level(s): {} error(s), {} warning(s)", to_check.len(), errors, warnings);
+    if errors > 0 { 1 } else { 0 }
+}
+
+// validate: everything we can tell is wrong with a level just by looking at it
+pub(crate) fn validate(level: &Level) -> Vec<Problem> {
+    let mut problems: Vec<Problem> = vec!();
+    let solids: Vec<(&LevelObject, Solid)> = level.objects.iter()
+        .filter_map(|o| solid_collider(o).map(|c| (o, c)))
+        .collect();
+    let hazards: Vec<(&LevelObject, RectCollider)> = level.objects.iter()
+        .filter_map(|o| match o { LevelObject::Hazard(h) => Some((o, h.collider())), _ => None })
+        .collect();
+    let (level_width, level_height) = level.size();
+    let level_size = (level_width as f32, level_height as f32);
+
+    if !level.objects.iter().any(|o| matches!(o, LevelObject::Start { .. })) {
+        problems.push(Problem { severity: Severity::Error, object: String::from("level"), message: String::from("there is no start point") });
+    }
+
+    for (i, obj) in level.objects.iter().enumerate() {
+        // everything has to be somewhere the player can get to
+        for rect in bounds(obj) {
+            check_in_level(obj, &rect, level_size, &mut problems);
+        }
+
+        match *obj {
+            LevelObject::PortalBlock(_) | LevelObject::NonPortalBlock(_) | LevelObject::PortalGlass(_) | LevelObject::Slope { .. } => {
+                let mine = solid_collider(obj).unwrap();
+                // only compare against the objects after this one so each overlap is reported once
+                for other in level.objects.iter().skip(i + 1) {
+                    if solid_collider(other).is_some_and(|theirs| mine.overlaps(&theirs)) {
+                        problems.push(Problem::warning(obj, format!("overlaps {}", other)));
+                    }
+                }
+            }
+            LevelObject::Start { x, y } => {
+                let player = RectCollider::new(x as f32, y as f32, PLAYER_WIDTH, PLAYER_HEIGHT);
+                if let Some((wall, _)) = solids.iter().find(|(_, c)| c.is_touching(&player)) {
+                    problems.push(Problem::error(obj, format!("the player starts inside {}", wall)));
+                }
+                if let Some((hazard, _)) = hazards.iter().find(|(_, c)| c.is_touching(&player)) {
+                    problems.push(Problem::error(obj, format!("the player starts in {}, so they die over and over", hazard)));
+                }
+            }
+            LevelObject::Checkpoint { x, y, .. } => {
+                // the player comes back standing at the foot of the flag, the way CheckpointController puts them
+                let player = RectCollider::new(x as f32, (y + (TILE_SIZE*2) as i32) as f32 - PLAYER_HEIGHT, PLAYER_WIDTH, PLAYER_HEIGHT);
+                if let Some((wall, _)) = solids.iter().find(|(_, c)| c.is_touching(&player)) {
+                    problems.push(Problem::error(obj, format!("the player respawns inside {}", wall)));
+                }
+                if let Some((hazard, _)) = hazards.iter().find(|(_, c)| c.is_touching(&player)) {
+                    problems.push(Problem::error(obj, format!("the player respawns in {}, so they die over and over", hazard)));
+                }
+            }
+            LevelObject::Block { x, y } | LevelObject::Ball { x, y } => {
+                let name = if let LevelObject::Ball { .. } = obj { "ball" } else { "block" };
+                let block = RectCollider::new(x as f32, y as f32, BLOCK_SIZE, BLOCK_SIZE);
+                if let Some((wall, _)) = solids.iter().find(|(_, c)| c.is_touching(&block)) {
+                    problems.push(Problem::error(obj, format!("the {} starts inside {}", name, wall)));
+                } else {
+                    // drop it straight down, the way ObjectController does, and see if anything catches it. a ball can
+                    // roll off whatever it lands on, but one that doesn't land at all is always lost
+                    let fall = RectCollider::new(x as f32, y as f32, BLOCK_SIZE, level_size.1 - y as f32);
+                    if !solids.iter().any(|(_, c)| c.is_touching(&fall)) {
+                        problems.push(Problem::error(obj, format!("there is nothing under the {}, it falls out of the world", name)));
+                    }
+                }
+            }
+            LevelObject::GatePlate { gate_x, gate_y, gate_length, gate_vertical, .. } => {
+                problems.extend(check_gate(obj, gate_x, gate_y, gate_length, gate_vertical, &solids, level_size));
+            }
+            LevelObject::Gate { x, y, length, vertical, .. } => {
+                problems.extend(check_gate(obj, x, y, length, vertical, &solids, level_size));
+            }
+            LevelObject::Platform(ref platform) if platform.speed == 0 => {
+                problems.push(Problem::warning(obj, String::from("the platform has a speed of 0, so it never moves")));
+            }
+            LevelObject::Size { width, height } if width < WINDOW_WIDTH || height < WINDOW_HEIGHT => {
+                problems.push(Problem::error(obj, format!("the level is smaller than the {}x{} window", WINDOW_WIDTH, WINDOW_HEIGHT)));
+            }
+            LevelObject::Timer { on, off, .. } if on == 0 && off == 0 => {
+                problems.push(Problem::error(obj, String::from("the timer is on for 0 ms and off for 0 ms")));
+            }
+            LevelObject::Rules(ref rules) => {
+                // some rules take away both ways of doing something
+                for (a, b) in [(Rule::WandOnly, Rule::PotionsOnly), (Rule::BlueOnly, Rule::OrangeOnly)].iter() {
+                    if rules.contains(a) && rules.contains(b) {
+                        problems.push(Problem::error(obj, format!("{} and {} can't both be on, the player couldn't make any portals", a.name(), b.name())));
+                    }
+                }
+            }
+            LevelObject::Zone { width, height, .. } if width == 0 || height == 0 => {
+                problems.push(Problem::warning(obj, String::from("the zone has no area, so nothing can stand in it")));
+            }
+            _ => {}
+        }
+
+        // something listening to a wire that nothing drives is stuck off forever
+        for wire in obj.wires_in() {
+            if wire != ALWAYS_ON && !level.objects.iter().any(|o| o.wire_out() == Some(wire)) {
+                problems.push(Problem::warning(obj, format!("nothing turns on wire {}, so it's always off", wire)));
+            }
+        }
+    }
+    problems
+}
+
+// check_gate: a gate has to fit between the walls around it and close off the gap between them
+fn check_gate(obj: &LevelObject, x: i32, y: i32, length: i32, vertical: bool, solids: &[(&LevelObject, Solid)], level_size: (f32, f32)) -> Vec<Problem> {
+    if length <= 0 {
+        return vec!(Problem::error(obj, String::from("the gate has no length")));
+    }
+    let mut problems: Vec<Problem> = vec!();
+    // the same collider GateController uses for a closed gate
+    let gate = if vertical {
+        RectCollider::new((x+6) as f32, y as f32, 44.0, length as f32)
+    } else {
+        RectCollider::new(x as f32, (y+6) as f32, length as f32, 44.0)
+    };
+    if let Some((wall, _)) = solids.iter().find(|(_, c)| c.is_touching(&gate)) {
+        problems.push(Problem::error(obj, format!("the gate is too long to fit, it runs into {}", wall)));
+    }
+    // both ends of a gate should meet a wall, otherwise the player can go around it
+    let (start, end) = if vertical {
+        ((gate.x() + 22.0, gate.y() - 1.0), (gate.x() + 22.0, gate.y() + gate.height() + 1.0))
+    } else {
+        ((gate.x() - 1.0, gate.y() + 22.0), (gate.x() + gate.width() + 1.0, gate.y() + 22.0))
+    };
+    for (end_name, point) in [("start", start), ("end", end)].iter() {
+        let in_level = point.0 > 0.0 && point.0 < level_size.0 && point.1 > 0.0 && point.1 < level_size.1;
+        if in_level && !solids.iter().any(|(_, c)| c.contains_point(point.0, point.1)) {
+            problems.push(Problem::warning(obj, format!("the {} of the gate doesn't meet a wall, so it leaves a gap", end_name)));
+        }
+    }
+    problems
+}
+
+// Solid: the shape of something the player can't walk through
+#[derive(Copy, Clone)]
+enum Solid {
+    Rect(RectCollider),
+    Slope(SlopeCollider),
+}
+
+impl Solid {
+    fn is_touching(&self, other: &RectCollider) -> bool {
+        match self {
+            Solid::Rect(r) => r.is_touching(other),
+            Solid::Slope(s) => s.is_touching(other),
+        }
+    }
+
+    fn contains_point(&self, x: f32, y: f32) -> bool {
+        match self {
+            Solid::Rect(r) => r.contains_point(x, y),
+            Solid::Slope(s) => s.contains_point(x, y),
+        }
+    }
+
+    // overlaps: close enough for two slopes, which only overlap if each one reaches into the other's box
+    fn overlaps(&self, other: &Solid) -> bool {
+        match (self, other) {
+            (_, Solid::Rect(r)) => self.is_touching(r),
+            (Solid::Rect(r), _) => other.is_touching(r),
+            (Solid::Slope(a), Solid::Slope(b)) => a.is_touching(&b.bounding_rect()) && b.is_touching(&a.bounding_rect()),
+        }
+    }
+}
+
+// solid_collider: the collider of anything the player can't walk through (platforms where they start out)
+fn solid_collider(obj: &LevelObject) -> Option<Solid> {
+    match *obj {
+        LevelObject::PortalBlock(s) | LevelObject::NonPortalBlock(s) | LevelObject::PortalGlass(s)
+        | LevelObject::Platform(Platform { surface: s, .. }) => Some(Solid::Rect(s.collider())),
+        LevelObject::Slope { surface, rises_right } => Some(Solid::Slope(surface.slope_collider(rises_right))),
+        _ => None,
+    }
+}
+
+// bounds: the rectangles an object takes up on screen
+fn bounds(obj: &LevelObject) -> Vec<RectCollider> {
+    match *obj {
+        // a platform has to stay on screen the whole way along its path
+        LevelObject::Platform(ref p) => {
+            let start = p.surface.collider();
+            p.waypoints.iter()
+                .map(|&(x, y)| RectCollider::new(x as f32, y as f32, start.width(), start.height()))
+                .chain(std::iter::once(start))
+                .collect()
+        }
+        LevelObject::PortalBlock(s) | LevelObject::NonPortalBlock(s) | LevelObject::PortalGlass(s) | LevelObject::Grid(s)
+        | LevelObject::Slope { surface: s, .. } | LevelObject::Hazard(Hazard { surface: s, .. }) => vec!(s.collider()),
+        LevelObject::GatePlate { plate_x, plate_y, gate_x, gate_y, gate_length, gate_vertical } => {
+            vec!(tile(plate_x, plate_y), gate_bounds(gate_x, gate_y, gate_length, gate_vertical))
+        }
+        LevelObject::Plate { x, y, .. } | LevelObject::Button { x, y, .. } | LevelObject::Light { x, y, .. } => vec!(tile(x, y)),
+        LevelObject::Gate { x, y, length, vertical, .. } => vec!(gate_bounds(x, y, length, vertical)),
+        LevelObject::Zone { x, y, width, height, .. } => vec!(RectCollider::new(x as f32, y as f32, width as f32, height as f32)),
+        LevelObject::Size { .. } | LevelObject::Rules(_) | LevelObject::Timer { .. } | LevelObject::Logic { .. } => vec!(),
+        LevelObject::Start { x, y } => vec!(RectCollider::new(x as f32, y as f32, PLAYER_WIDTH, PLAYER_HEIGHT)),
+        LevelObject::Checkpoint { x, y, .. } => vec!(RectCollider::new(x as f32, y as f32, TILE_SIZE as f32, (TILE_SIZE*2) as f32)),
+        LevelObject::Block { x, y } | LevelObject::Ball { x, y } => vec!(RectCollider::new(x as f32, y as f32, BLOCK_SIZE, BLOCK_SIZE)),
+        LevelObject::Door(d) => vec!(RectCollider::new(d.x as f32, d.y as f32, d.width as f32, d.height as f32)),
+    }
+}
+
+fn tile(x: i32, y: i32) -> RectCollider {
+    RectCollider::new(x as f32, y as f32, TILE_SIZE as f32, TILE_SIZE as f32)
+}
+
+fn gate_bounds(x: i32, y: i32, length: i32, vertical: bool) -> RectCollider {
+    if vertical {
+        RectCollider::new(x as f32, y as f32, TILE_SIZE as f32, length as f32)
+    } else {
+        RectCollider::new(x as f32, y as f32, length as f32, TILE_SIZE as f32)
+    }
+}
+
+// check_in_level: a rectangle outside the level is an error. one that hangs off the edge by a whole
+// tile or more is only a warning, since the window is 11.25 tiles tall and the last row always hangs off a bit
+fn check_in_level(obj: &LevelObject, rect: &RectCollider, level_size: (f32, f32), problems: &mut Vec<Problem>) {
+    let level = RectCollider::new(0.0, 0.0, level_size.0, level_size.1);
+    if !rect.is_touching(&level) {
+        problems.push(Problem::error(obj, format!("is completely outside the {}x{} level", level_size.0, level_size.1)));
+        return;
+    }
+    let overhang = (-rect.x())
+        .max(-rect.y())
+        .max(rect.x() + rect.width() - level_size.0)
+        .max(rect.y() + rect.height() - level_size.1);
+    if overhang >= TILE_SIZE as f32 {
+        problems.push(Problem::warning(obj, format!("hangs {} px off the edge of the {}x{} level", overhang, level_size.0, level_size.1)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::levels::parse_level_str;
+
+    // a floor across the bottom of the window and a ceiling across the top, with the player standing on the floor
+    const ROOM: &str = "nonportalblock-0-656-20-1\nnonportalblock-0-0-20-1\nstart-100-558\n";
+
+    fn check(extra: &str) -> Vec<Problem> {
+        validate(&parse_level_str("test.txt", &format!("{}{}", ROOM, extra)).unwrap())
+    }
+
+    fn has(problems: &[Problem], severity: Severity, object: &str, message: &str) -> bool {
+        problems.iter().any(|p| p.severity == severity && p.object == object && p.message == message)
+    }
+
+    #[test]
+    fn a_good_level_has_no_problems() {
+        assert!(check("").is_empty());
+    }
+
+    #[test]
+    fn start_inside_a_wall() {
+        let problems = check("start-100-600\n");
+        assert!(has(&problems, Severity::Error, "start-100-600", "the player starts inside nonportalblock-0-656-20-1"));
+    }
+
+    #[test]
+    fn level_without_a_start() {
+        let problems = validate(&parse_level_str("test.txt", "nonportalblock-0-656-20-1\n").unwrap());
+        assert!(has(&problems, Severity::Error, "level", "there is no start point"));
+    }
+
+    #[test]
+    fn gate_that_doesnt_fit() {
+        let problems = check("gateplate-300-592-600-64-700-1\n");
+        assert!(has(&problems, Severity::Error, "gateplate-300-592-600-64-700-1", "the gate is too long to fit, it runs into nonportalblock-0-656-20-1"));
+        // floor to ceiling exactly is fine
+        assert!(check("gateplate-300-592-600-64-592-1\n").is_empty());
+    }
+
+    #[test]
+    fn gate_that_leaves_a_gap() {
+        let problems = check("gateplate-300-592-600-64-400-1\n");
+        assert!(has(&problems, Severity::Warning, "gateplate-300-592-600-64-400-1", "the end of the gate doesn't meet a wall, so it leaves a gap"));
+        assert!(!has(&problems, Severity::Warning, "gateplate-300-592-600-64-400-1", "the start of the gate doesn't meet a wall, so it leaves a gap"));
+    }
+
+    #[test]
+    fn block_with_nothing_under_it() {
+        let problems = validate(&parse_level_str("test.txt", "nonportalblock-0-656-10-1\nstart-100-558\nblock-900-100\nblock-300-100\n").unwrap());
+        assert!(has(&problems, Severity::Error, "block-900-100", "there is nothing under the block, it falls out of the world"));
+        assert!(problems.iter().all(|p| p.object != "block-300-100"));
+    }
+
+    #[test]
+    fn object_outside_the_level() {
+        let problems = check("lava-2000-300-1-1\nlava-1216-300-2-1\n");
+        assert!(has(&problems, Severity::Error, "lava-2000-300-1-1", "is completely outside the 1280x720 level"));
+        assert!(has(&problems, Severity::Warning, "lava-1216-300-2-1", "hangs 64 px off the edge of the 1280x720 level"));
+    }
+
+    #[test]
+    fn zero_length_timer() {
+        let problems = check("timer-1-0-0\n");
+        assert!(has(&problems, Severity::Error, "timer-1-0-0", "the timer is on for 0 ms and off for 0 ms"));
+        // times too long to add together are still fine
+        assert!(check("timer-1-4294967295-1\n").is_empty());
+    }
+}
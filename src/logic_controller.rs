@@ -0,0 +1,106 @@
+use std::collections::HashSet;
+
+use crate::button_controller::ButtonController;
+use crate::levels::{LogicNode, ALWAYS_ON};
+use crate::plate_controller::PlateController;
+use crate::rect_collider::RectCollider;
+
+// LogicController: the level's wiring. every frame the inputs (plates, buttons, timers and zones) turn their wires on,
+// then the logic nodes work out the rest of the wires from those. gates, platforms, lights and doors
+// look up the wire they're on to see what they should be doing
+#[derive(Default)]
+pub struct LogicController {
+    // wire, milliseconds on, milliseconds off
+    timers: Vec<(u32, u128, u128)>,
+    zones: Vec<(u32, RectCollider)>,
+    nodes: Vec<(u32, LogicNode)>,
+    // when each delay node's input was last on
+    last_on: Vec<Option<u128>>,
+    on: HashSet<u32>,
+    start_time: Option<u128>
+}
+
+impl LogicController {
+    pub fn add_timer(&mut self, wire: u32, on: u32, off: u32) {
+        self.timers.push((wire, on as u128, off as u128));
+    }
+
+    pub fn add_zone(&mut self, wire: u32, zone: RectCollider) {
+        self.zones.push((wire, zone));
+    }
+
+    pub fn add_node(&mut self, wire: u32, node: LogicNode) {
+        self.nodes.push((wire, node));
+        self.last_on.push(None);
+    }
+
+    pub fn is_on(&self, wire: u32) -> bool {
+        wire == ALWAYS_ON || self.on.contains(&wire)
+    }
+
+    // update: works out which wires are on this frame. time is in milliseconds, and timers count from the first update
+    pub fn update(&mut self, time: u128, plates: &[PlateController], buttons: &[ButtonController], players: &[RectCollider]) {
+        let elapsed = time - *self.start_time.get_or_insert(time);
+        let mut inputs: HashSet<u32> = HashSet::new();
+        for plate in plates {
+            if plate.plate_pressed() {
+                inputs.insert(plate.wire());
+            }
+        }
+        for button in buttons {
+            if button.is_on(time) {
+                inputs.insert(button.wire());
+            }
+        }
+        for &(wire, on, off) in &self.timers {
+            if on + off > 0 && elapsed % (on + off) < on {
+                inputs.insert(wire);
+            }
+        }
+        for (wire, zone) in &self.zones {
+            if players.iter().any(|p| p.is_touching(zone)) {
+                inputs.insert(*wire);
+            }
+        }
+
+        // a delay is still on if its input was on recently enough, whatever its input is doing now
+        let held: Vec<bool> = self.nodes.iter().zip(&self.last_on)
+            .map(|((_, node), last_on)| match node {
+                LogicNode::Delay { ms, .. } => last_on.is_some_and(|t| elapsed - t < *ms as u128),
+                _ => false,
+            })
+            .collect();
+
+        // nodes can be listed in any order, so keep going over them until nothing changes.
+        // a loop through a not node never settles down, so give up after a pass per node
+        let mut on = inputs.clone();
+        for _ in 0..=self.nodes.len() {
+            let mut next = inputs.clone();
+            let is_on = |w: &u32| *w == ALWAYS_ON || on.contains(w);
+            for (i, (wire, node)) in self.nodes.iter().enumerate() {
+                let value = match node {
+                    LogicNode::And(wires) => wires.iter().all(is_on),
+                    LogicNode::Or(wires) => wires.iter().any(is_on),
+                    LogicNode::Not(input) => !is_on(input),
+                    LogicNode::Delay { input, .. } => is_on(input) || held[i],
+                };
+                if value {
+                    next.insert(*wire);
+                }
+            }
+            let settled = next == on;
+            on = next;
+            if settled { break; }
+        }
+
+        // remember when the delays' inputs were on
+        for ((_, node), last_on) in self.nodes.iter().zip(self.last_on.iter_mut()) {
+            if let LogicNode::Delay { input, .. } = node {
+                if *input == ALWAYS_ON || on.contains(input) {
+                    *last_on = Some(elapsed);
+                }
+            }
+        }
+        self.on = on;
+    }
+}
@@ -7,36 +7,68 @@ use sdl2::mouse::MouseUtil;
 use sdl2::render::WindowCanvas;
 
 mod credits;
+mod editor;
 mod game;
 mod player;
 mod physics_controller;
 mod rect_collider;
+mod rigid_body;
+mod physics_world;
 mod menu;
 mod animation_controller;
+mod camera;
+mod clock;
 mod portal_controller;
 mod networking;
 mod hint_system;
 mod object_controller;
+mod ball_controller;
 mod portal_traversible;
 mod levels;
 mod plate_controller;
+mod gate_controller;
+mod button_controller;
+mod logic_controller;
+mod platform_controller;
+mod checkpoint_controller;
 mod remote_player;
+mod resources;
+mod slope_collider;
+mod circle_collider;
 
 fn main() {
+	let mut args: Vec<String> = env::args().collect();
+	// where to find assets/ and the levels, if not next to the executable or in the working directory
+	resources::init(take_option(&mut args, "--root"));
+
+	// checking levels doesn't need a window, so do it before starting SDL
+	if args.len() >= 2 && &args[1] == "validate" {
+		process::exit(levels::validator::run(&args[2..]));
+	}
+	if args.len() == 4 && &args[1] == "import" {
+		process::exit(import_tiled_map(&args[2], &args[3]));
+	}
+
 	let (sdl_cxt, wincan) = setup_sdl_and_window();
 	let event_pump = sdl_cxt.event_pump().unwrap();
 	let mouse = sdl_cxt.mouse();
 
-	perform_start_logic(wincan, event_pump, mouse)
+	perform_start_logic(wincan, event_pump, mouse, args)
 }
 
-fn perform_start_logic(wincan: WindowCanvas, event_pump: EventPump, mouse: MouseUtil) {
-	let args: Vec<String> = env::args().collect();
+fn perform_start_logic(wincan: WindowCanvas, event_pump: EventPump, mouse: MouseUtil, mut args: Vec<String>) {
+	// which level pack from src/levels/packs.txt to play
+	let pack = take_option(&mut args, "--pack").unwrap_or_else(|| "campaign".to_string());
+	// reload the level whenever its file is saved, for working on levels
+	let watch = take_flag(&mut args, "--watch");
 	let mut multiplayer = None;
 	if args.len() < 2 || (args.len() == 2 && &args[1] == "singleplayer") {
-		menu::show_menu(wincan, event_pump, mouse, multiplayer);
+		menu::show_menu(wincan, event_pump, mouse, multiplayer, &pack, watch);
 	} else if args.len() == 2 && &args[1] == "credits" {
 		credits::show_credits(wincan, event_pump);
+	} else if (args.len() == 2 || args.len() == 3) && &args[1] == "editor" {
+		let file = args.get(2).map_or(editor::DEFAULT_LEVEL, |f| f.as_str());
+		editor::run(wincan, event_pump, mouse, file);
 	} else if args.len() == 3 && &args[1] == "multiplayer" {
 		if &args[2] == "1" || &args[2] == "p1" {
 			multiplayer = Some(networking::Multiplayer::new(networking::Mode::MultiplayerPlayer1));
@@ -46,14 +78,56 @@ fn perform_start_logic(wincan: WindowCanvas, event_pump: EventPump, mouse: Mouse
 			println!("Must use: multiplayer 1 or multiplayer 2");
 			process::exit(0);
 		}
-		menu::show_menu(wincan, event_pump, mouse, multiplayer);
+		menu::show_menu(wincan, event_pump, mouse, multiplayer, &pack, watch);
 	} else {
 		println!("Invalid Arguments. Your options are:\n\
-		singleplayer (default)\nmultiplayer 1\nmultiplayer 2\nor credits.");
+		singleplayer (default)\nmultiplayer 1\nmultiplayer 2\neditor {{level file}}\nvalidate {{level files (all levels if none)}}\nimport {{Tiled map}} {{level file}}\nor credits.\n\
+		Add --pack {{name}} to play a level pack other than the campaign,\n\
+		--watch to reload the level you're on whenever its file is saved,\n\
+		and --root {{folder}} (or set {}) if the game can't find its assets.", resources::ROOT_ENV_VAR);
 		process::exit(0);
 	}
 }
 
+// take_option: pulls a "--flag value" pair out of the arguments, so the rest can be matched as before
+fn take_option(args: &mut Vec<String>, flag: &str) -> Option<String> {
+	let i = args.iter().position(|a| a == flag)?;
+	if i + 1 >= args.len() {
+		println!("{} needs a value after it", flag);
+		process::exit(0);
+	}
+	let value = args.remove(i + 1);
+	args.remove(i);
+	Some(value)
+}
+
+// import_tiled_map: converts a map made in Tiled into a level file in the levels folder
+fn import_tiled_map(map_file: &str, level_file: &str) -> i32 {
+	let result = levels::import_map(std::path::Path::new(map_file))
+		.and_then(|level| levels::save_level(level_file, &level).map(|_| level));
+	match result {
+		Ok(level) => {
+			println!("wrote {} objects to {}", level.objects.len(), resources::level_path(level_file).display());
+			0
+		}
+		Err(e) => {
+			println!("{}", e);
+			1
+		}
+	}
+}
+
+// take_flag: pulls a "--flag" with no value out of the arguments, saying whether it was there
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+	match args.iter().position(|a| a == flag) {
+		Some(i) => {
+			args.remove(i);
+			true
+		}
+		None => false,
+	}
+}
+
 fn setup_sdl_and_window() -> (Sdl, WindowCanvas) {
 	let sdl_cxt = sdl2::init().unwrap();
 	let video_subsys = sdl_cxt.video().unwrap();
@@ -61,7 +135,8 @@ fn setup_sdl_and_window() -> (Sdl, WindowCanvas) {
 		.build()
 		.map_err(|e| e.to_string())
 		.unwrap();
-	let wincan = window.into_canvas().accelerated();
+	// vsync keeps frames in step with the screen, the game itself ticks at a fixed rate either way
+	let wincan = window.into_canvas().accelerated().present_vsync();
 	let wincan = wincan.build()
 		.map_err(|e| e.to_string())
 		.unwrap();
@@ -4,24 +4,32 @@ use sdl2::keyboard::Keycode;
 use sdl2::mouse::MouseUtil;
 use sdl2::render::WindowCanvas;
 
+use crate::editor;
 use crate::game;
+use crate::resources;
 use crate::networking::Multiplayer;
 
-pub(crate) fn show_menu(mut wincan: WindowCanvas, mut event_pump: sdl2::EventPump, mouse: MouseUtil, multiplayer: Option<Multiplayer>)
+pub(crate) fn show_menu(mut wincan: WindowCanvas, mut event_pump: sdl2::EventPump, mouse: MouseUtil, multiplayer: Option<Multiplayer>, pack_name: &str, watch: bool)
 {
     let texture_creator = wincan.texture_creator();
 
-    let start = texture_creator.load_texture("assets/out_of_game/menu/start_screen.png").unwrap();
+    let start = texture_creator.load_texture(resources::path("assets/out_of_game/menu/start_screen.png")).unwrap();
 
     wincan.copy(&start, None, None).ok();
     wincan.present();
 
+    let mut open_editor = false;
     'menu_loop: loop {
         for event in event_pump.poll_iter() {
             match event {
                 Event::Quit{..} | Event::KeyDown{keycode: Some(Keycode::Escape), ..} => break 'menu_loop,
                 Event::KeyDown{keycode: Some(k), ..} => {
                     match k {
+                        // E opens the level editor instead of starting the game
+                        Keycode::E if multiplayer.is_none() => {
+                            open_editor = true;
+                            break 'menu_loop;
+                        }
                         _ => break 'menu_loop,
                     }
                 }
@@ -29,5 +37,9 @@ pub(crate) fn show_menu(mut wincan: WindowCanvas, mut event_pump: sdl2::EventPum
             }
         }
     }
-    game::run(wincan, event_pump, mouse, multiplayer).ok();
+    if open_editor {
+        editor::run(wincan, event_pump, mouse, editor::DEFAULT_LEVEL);
+    } else {
+        game::run(wincan, event_pump, mouse, multiplayer, pack_name, watch).ok();
+    }
 }
\ No newline at end of file
@@ -3,7 +3,7 @@ use std::str::FromStr;
 use crate::player::Player;
 use crate::object_controller::ObjectController;
 
-pub const PACKET_SIZE: usize = 80;
+pub const PACKET_SIZE: usize = 84;
 const DEBUG: bool = false;
 
 #[derive(Copy, Clone)]
@@ -71,7 +71,7 @@ impl Multiplayer {
 
 pub fn pack_data(
     player: &mut Player,
-    block: &ObjectController,
+    blocks: &[ObjectController],
     multiplayer: &Option<Multiplayer>,
 ) -> Vec<u8> {
 
@@ -104,11 +104,16 @@ pub fn pack_data(
         }
     }
 
-    //Block Information
-    let block_x: [u8; 4] = block.x().to_le_bytes();
-    let block_y: [u8; 4] = block.y().to_le_bytes();
-    let carried = block.carried as u32;
+    //Block Information (the block we're carrying, or the first block if we aren't carrying one)
+    let carried_index = blocks.iter().position(|b| b.carried);
+    let (block_x, block_y, carried) = match carried_index.or(if blocks.is_empty() { None } else { Some(0) }) {
+        Some(i) => (blocks[i].x(), blocks[i].y(), blocks[i].carried as u32),
+        None => (0, 0, 0),
+    };
+    let block_x: [u8; 4] = block_x.to_le_bytes();
+    let block_y: [u8; 4] = block_y.to_le_bytes();
     let block_carried: [u8; 4] = carried.to_le_bytes();
+    let block_index: [u8; 4] = carried_index.map_or(-1, |i| i as i32).to_le_bytes();
 
     //Wand Information
     let wand_x: [u8; 4] = player.portal.wand_x().to_le_bytes();
@@ -151,6 +156,7 @@ pub fn pack_data(
         potion_y,
         potion_rotation,
         which_potion,
+        block_index,
     ].concat();
     if DEBUG { println!("{:?}", &buf); }
 
@@ -254,7 +260,7 @@ pub fn unpack_portal_data(buf: &mut [u8; PACKET_SIZE]) -> (f32, f32, f32) {
     (x1,y1,rotation1)
 }
 
-pub(crate) fn unpack_block_data(buf: &mut [u8; PACKET_SIZE]) -> (i32, i32, bool){
+pub(crate) fn unpack_block_data(buf: &mut [u8; PACKET_SIZE]) -> (i32, i32, bool, i32){
     let mut block_x: [u8; 4] = [0; 4];
     for i in 40..44 {
         block_x[i-40] = buf[i];
@@ -270,6 +276,11 @@ pub(crate) fn unpack_block_data(buf: &mut [u8; PACKET_SIZE]) -> (i32, i32, bool)
         carried[i-48] = buf[i];
     }
 
+    let mut block_index: [u8; 4] = [0; 4];
+    for i in 80..84 {
+        block_index[i-80] = buf[i];
+    }
+
     let block_x = i32::from_le_bytes(block_x);
     let block_y = i32::from_le_bytes(block_y);
     let carried = i32::from_le_bytes(carried);
@@ -277,8 +288,9 @@ pub(crate) fn unpack_block_data(buf: &mut [u8; PACKET_SIZE]) -> (i32, i32, bool)
         0 => false,
         _ => true,
     };
+    let block_index = i32::from_le_bytes(block_index);
 
-    (block_x, block_y, carried)
+    (block_x, block_y, carried, block_index)
 }
 
 pub(crate) fn unpack_wand_data(buf: &mut [u8; PACKET_SIZE]) -> (i32, i32, f32) {
@@ -1,15 +1,31 @@
 //ObjectController: controls physics and collision for carryable objects (UNFINISHED)
+use crate::levels::WINDOW_WIDTH;
 use crate::rect_collider::RectCollider;
+use crate::rigid_body::RigidBody;
+use crate::slope_collider::SlopeCollider;
 use crate::player::Player;
+use crate::platform_controller::PlatformController;
 use crate::remote_player::RemotePlayer;
 
+// blocks are lighter than the player, so they can be pushed around
+const MASS: f32 = 30.0;
+// how much sideways speed a block loses each tick sliding along the ground
+const FRICTION: f32 = 0.5;
+// and how much of its speed it keeps when it bounces
+const RESTITUTION: f32 = 0.2;
+const GRAVITY: f32 = 1.0;
+const MAX_FALL_SPEED: f32 = 40.0;
+// how far from the player a block can be and still get picked up
+const REACH: f32 = 8.0;
+
 pub struct ObjectController {
-    collider: RectCollider,
+    body: RigidBody,
     obstacles: Vec<RectCollider>,
+    slopes: Vec<SlopeCollider>,
+    // how far right the block can go, the width of the level
+    level_width: f32,
     pub carried: bool,
-    in_air: bool,
     new_level: bool,
-    fall_speed: f32,
     pub offset: (f32, f32),
     start_x: f32,
     start_y: f32,
@@ -19,35 +35,39 @@ impl ObjectController {
     pub fn new(_collider: RectCollider) -> ObjectController
     {
         ObjectController {
-            collider: _collider,
+            body: RigidBody::new(_collider, MASS, FRICTION, RESTITUTION),
             obstacles: vec!(),
+            slopes: vec!(),
+            level_width: WINDOW_WIDTH as f32,
             carried: false,
-            in_air: false,
             new_level: false,
-            fall_speed: 0.0,
             offset: (0.0, 0.0),
             start_x: 0.0,
             start_y: 0.0,
         }
     }
 
-    pub fn x(&self) -> i32 { self.collider.x() as i32}
-    pub fn y(&self) -> i32 { self.collider.y() as i32}
+    pub fn x(&self) -> i32 { self.body.x() as i32}
+    pub fn y(&self) -> i32 { self.body.y() as i32}
     // pub fn carried(&self) -> bool { self.carried }
-    pub fn in_air(&self) -> bool { self.in_air }
+    pub fn in_air(&self) -> bool { !self.body.is_grounded() }
     pub fn new_level(&self) -> bool { self.new_level }
-    pub fn fall_speed(&self) -> f32 { self.fall_speed }
-    pub fn collider(&self) -> RectCollider { self.collider }
+    pub fn collider(&self) -> RectCollider { self.body.collider() }
+    pub fn body_mut(&mut self) -> &mut RigidBody { &mut self.body }
+    pub fn set_level_width(&mut self, _level_width: u32) { self.level_width = _level_width as f32 }
 
-    pub fn reset_colliders(&mut self) { self.obstacles = vec!(); }
     pub fn add_collider(&mut self, wall: RectCollider) {
         self.obstacles.push(wall);
     }
 
+    pub fn add_slope(&mut self, slope: SlopeCollider) {
+        self.slopes.push(slope);
+    }
+
     pub fn picked_up(&mut self, player: &Player) {
         self.carried = true;
-        self.in_air = true;
-        self.collider.set_y((self.y() - 20) as f32);
+        self.body.set_grounded(false);
+        self.body.set_y((self.y() - 20) as f32);
         self.offset = get_offset(self.collider(), player.collider);
     }
 
@@ -64,45 +84,70 @@ impl ObjectController {
         self.start_y = y;
     }
 
-    pub fn update(&mut self, player: &Player, remote_player: Option<RemotePlayer>) {
+    pub fn update(&mut self, player: &Player, remote_player: Option<RemotePlayer>, platforms: &[PlatformController]) {
         if self.new_level {
             self.new_level = false;
             self.carried = false;
-            self.in_air = true;
-            self.collider.set_x(self.start_x);
-            self.collider.set_y(self.start_y);
+            self.body.set_grounded(false);
+            self.body.set_x(self.start_x);
+            self.body.set_y(self.start_y);
+            self.body.set_vx(0.0);
+            self.body.set_vy(0.0);
         }
         if remote_player.is_some() {
             let block_data = remote_player.unwrap().block_data;
             let remote_player_data = remote_player.unwrap().player_data;
             let carried = block_data.2;
             if carried {
-                self.collider.set_x((remote_player_data.0-self.offset.0) as f32);
-                self.collider.set_y((remote_player_data.1-self.offset.1) as f32);
+                self.body.set_x((remote_player_data.0-self.offset.0) as f32);
+                self.body.set_y((remote_player_data.1-self.offset.1) as f32);
             }
         }
         if self.carried {
-            self.collider.set_x((player.collider.x()-self.offset.0) as f32);
-            self.collider.set_y((player.collider.y()-self.offset.1) as f32);
-        } else if self.in_air {
-            self.fall_speed += 1.0;
-            let predict = RectCollider::new(self.x() as f32, self.y() as f32 + self.fall_speed(), self.collider.width(), self.collider.height());
-            let mut ground = 721.0;
-            for wall in &self.obstacles {
-                if predict.is_touching(&wall) { ground = wall.y(); }
+            self.body.set_x((player.collider.x()-self.offset.0) as f32);
+            self.body.set_y((player.collider.y()-self.offset.1) as f32);
+            self.body.set_vx(0.0);
+            self.body.set_vy(0.0);
+        } else {
+            // ride along with a platform we're sitting on
+            for p in platforms {
+                if p.carries(&self.collider()) {
+                    self.body.shift(p.moved().0, p.moved().1);
+                }
             }
-            if ground < 721.0 {
-                self.collider.set_y(ground-(64/2) as f32);
-                self.in_air = false;
-                self.fall_speed = 0.0;
-            }
-            else {
-                self.collider.set_y(self.y() as f32 + self.fall_speed());
+            // moving blocks land on the platforms as well as the level
+            let solids: Vec<RectCollider> = self.obstacles.iter().copied().chain(platforms.iter().map(|p| p.collider())).collect();
+            self.body.step(&solids, self.level_width, GRAVITY, MAX_FALL_SPEED);
+            // blocks don't slide, they stop wherever they land on a slope
+            for slope in &self.slopes {
+                let collider = self.body.collider();
+                if self.body.vy() >= 0.0 && slope.is_touching(&collider) {
+                    self.body.set_y(slope.resting_y(&collider) - collider.height());
+                    self.body.set_vx(0.0);
+                    self.body.set_vy(0.0);
+                    self.body.set_grounded(true);
+                }
             }
         }
     }
 }
 
+// nearest_block: finds the closest block the player can reach, so picking up works with several blocks around. the
+// player pushes blocks rather than walking into them, so they only have to be next to one
+pub fn nearest_block(blocks: &[ObjectController], player: &Player) -> Option<usize> {
+    let reach = RectCollider::new(player.collider.x() - REACH, player.collider.y() - REACH, player.collider.width() + 2.0*REACH, player.collider.height() + 2.0*REACH);
+    let player_center = (player.collider.x() + player.collider.width()/2.0, player.collider.y() + player.collider.height()/2.0);
+    let distance = |b: &ObjectController| {
+        let c = b.collider();
+        ((c.x() + c.width()/2.0 - player_center.0).powf(2.0) + (c.y() + c.height()/2.0 - player_center.1).powf(2.0)).powf(0.5)
+    };
+    blocks.iter()
+        .enumerate()
+        .filter(|(_, b)| reach.is_touching(&b.collider()))
+        .min_by(|(_, a), (_, b)| distance(a).partial_cmp(&distance(b)).unwrap())
+        .map(|(i, _)| i)
+}
+
 pub fn get_offset(inner: RectCollider, outer: RectCollider) -> (f32, f32) {
     let y = outer.y() - inner.y();
     let mut x = 0.0;
@@ -1,77 +1,98 @@
-use std::time::{Duration, SystemTime};
-
+use crate::levels::{Rule, WINDOW_WIDTH};
 use crate::rect_collider::RectCollider;
-use crate::plate_controller::PlateController;
+use crate::rigid_body::RigidBody;
+use crate::gate_controller::GateController;
+use crate::platform_controller::PlatformController;
+use crate::slope_collider::SlopeCollider;
+
+// how much of gravity pulls us down a slope we're standing on. all of it makes slopes too slippery to walk up
+const SLIDE_FACTOR: f32 = 0.5;
+// we stand on slopes with the middle of our feet, not the corner of our collider
+const FEET_WIDTH: f32 = 20.0;
+// a slope only stops us like a wall if it's higher than this above our feet
+const STEP_HEIGHT: f32 = 32.0;
+// how heavy the player is, next to the blocks they push around
+const MASS: f32 = 70.0;
 
 //#[derive(Copy, Clone)]
 pub struct PhysicsController {
     start_x: f32,
     start_y: f32,
-    x: f32,
-    y: f32,
-    speed: f32,
+    // where we are and how fast we're going
+    body: RigidBody,
     max_speed: f32,
     pub acceleration: f32,
     jump_speed: f32,
     jumps_used: i8,
-    last_jump_time: SystemTime,
-    last_ground_time: SystemTime,
+    // game times (see Clock), in ms
+    last_jump_time: u128,
+    last_ground_time: u128,
     max_jumps: i8,
-    stop_speed: f32,
-    fall_speed: f32,
+    // how many jumps the player gets when the level doesn't say otherwise
+    jump_limit: i8,
+    can_dash: bool,
+    // how far right the player can walk, the width of the level
+    level_width: f32,
     gravity: f32,
     max_fall_speed: f32,
-    is_grounded: bool,
     can_move: bool,
     dash_time: u128,
     pre_dash_speed: f32,
     curr_direction: i8, // 1 if facing right, 0 if facing left
-    colliders: Vec<RectCollider>
+    colliders: Vec<RectCollider>,
+    slopes: Vec<SlopeCollider>,
+    // the other bodies around us (the blocks), which we can stand on as well as the level
+    bodies: Vec<RectCollider>,
+    on_slope: bool
 }
 
 impl PhysicsController {
     pub fn new(_x: f32, _y:f32, _maxspeed: f32, _acceleration: f32, _jumpspeed:f32, _maxjumps: i8, _stopspeed: f32, _gravity: f32, _maxfallspeed: f32,  _colliders: Vec<RectCollider>)
         -> PhysicsController
     {
+        // we slow down at the same rate on the ground and in the air, and don't bounce off anything
+        let mut body = RigidBody::new(RectCollider::new(_x, _y, 69.0, 98.0), MASS, _stopspeed, 0.0);
+        body.set_drag(_stopspeed);
         PhysicsController {
             start_x: 0.0,
             start_y: 0.0,
-            x: _x,
-            y: _y,
-            speed: 0.0,
+            body,
             max_speed: _maxspeed,
             acceleration: _acceleration,
             jump_speed: _jumpspeed,
             jumps_used: _maxjumps,
-            last_jump_time: SystemTime::now(),
-            last_ground_time: SystemTime::now(),
+            last_jump_time: 0,
+            last_ground_time: 0,
             max_jumps: _maxjumps,
-            stop_speed: _stopspeed,
-            fall_speed: 0.0,
+            jump_limit: _maxjumps,
+            can_dash: true,
+            level_width: WINDOW_WIDTH as f32,
             gravity: _gravity,
             max_fall_speed: _maxfallspeed,
-            is_grounded: false,
             can_move: true,
             dash_time: 100,
             pre_dash_speed: 0.0,
             curr_direction: 1,
-            colliders: _colliders
+            colliders: _colliders,
+            slopes: vec!(),
+            bodies: vec!(),
+            on_slope: false
         }
     }
 
     //getters
     //pub fn start_x(&self) -> f32 { self.start_x }
     //pub fn start_y(&self) -> f32 { self.start_y }
-    pub fn x(&self) -> f32 { self.x }
-    pub fn y(&self) -> f32 { self.y }
-    pub fn position_rect(&self) -> (i32, i32, u32, u32) { (self.x as i32, self.y as i32, 69, 98)}
-    pub fn speed(&self) -> f32 { self.speed }
-    pub fn fall_speed(&self) -> f32 { self.fall_speed }
-    pub fn dash_time(&self) -> u128 { self.dash_time }
-    pub fn is_grounded(&self) -> bool { self.is_grounded }
+    pub fn x(&self) -> f32 { self.body.x() }
+    pub fn y(&self) -> f32 { self.body.y() }
+    pub fn speed(&self) -> f32 { self.body.vx() }
+    pub fn fall_speed(&self) -> f32 { self.body.vy() }
+    pub fn can_dash(&self) -> bool { self.can_dash }
+    pub fn is_grounded(&self) -> bool { self.body.is_grounded() }
     pub fn total_speed(&self) -> f32 {
-        self.speed.powf(2.0) + self.fall_speed.powf(2.0).powf(0.5)
+        self.speed().powf(2.0) + self.fall_speed().powf(2.0).powf(0.5)
     }
+    pub fn body_mut(&mut self) -> &mut RigidBody { &mut self.body }
     pub fn colliders(&self) -> Vec<RectCollider> {
         let mut return_vec: Vec<RectCollider> = vec!();
         for c in &self.colliders {
@@ -79,6 +100,7 @@ impl PhysicsController {
         }
         return_vec
     }
+    pub fn slopes(&self) -> Vec<SlopeCollider> { self.slopes.clone() }
 
     //setters
     pub fn reset_jumps(&mut self) { self.jumps_used = 0; }
@@ -86,170 +108,217 @@ impl PhysicsController {
     pub fn mobilize(&mut self) { self.can_move = true; }
     pub fn set_start_x(&mut self, _x: f32) { self.start_x = _x; }
     pub fn set_start_y(&mut self, _y: f32) { self.start_y = _y; }
-    pub fn set_x(&mut self, _x: f32) {self.x = _x}
-    pub fn set_y(&mut self, _y: f32) {self.y = _y}
-    pub fn set_speed(&mut self, _speed: f32) {self.speed = _speed}
-    pub fn set_fall_speed(&mut self, _fall_speed: f32) {self.fall_speed = _fall_speed}
+    pub fn set_x(&mut self, _x: f32) {self.body.set_x(_x)}
+    pub fn set_y(&mut self, _y: f32) {self.body.set_y(_y)}
+    pub fn set_speed(&mut self, _speed: f32) {self.body.set_vx(_speed)}
+    pub fn set_fall_speed(&mut self, _fall_speed: f32) {self.body.set_vy(_fall_speed)}
     pub fn set_jumps_used(&mut self, _jumps_used: i8) { self.jumps_used = _jumps_used }
-    pub fn reset_colliders(&mut self) { self.colliders = vec!(); }
+    pub fn set_level_width(&mut self, _level_width: u32) { self.level_width = _level_width as f32 }
+    pub fn set_bodies(&mut self, _bodies: Vec<RectCollider>) { self.bodies = _bodies }
+    pub fn set_size(&mut self, _width: f32, _height: f32) { self.body.set_size(_width, _height) }
+    pub fn reset_colliders(&mut self) {
+        self.colliders = vec!();
+        self.slopes = vec!();
+    }
     pub fn respawn(&mut self) {
-        self.x = self.start_x;
-        self.y = self.start_y;
+        self.body.set_x(self.start_x);
+        self.body.set_y(self.start_y);
     }
 
     pub fn add_collider(&mut self, new_collider: RectCollider) {
         self.colliders.push(new_collider);
     }
 
+    // restrict: takes away whatever movement the level doesn't allow, and gives back everything else
+    pub fn restrict(&mut self, rules: &[Rule]) {
+        self.can_dash = !rules.contains(&Rule::NoDash);
+        self.max_jumps = if rules.contains(&Rule::SingleJump) { 1 } else { self.jump_limit };
+    }
+
+    pub fn add_slope(&mut self, new_slope: SlopeCollider) {
+        self.slopes.push(new_slope);
+    }
+
+    // solids: everything we can't walk through. closed gates and moving platforms block us just like the level's
+    // walls do
+    pub fn solids(&self, gates: &[GateController], platforms: &[PlatformController])
+        -> Vec<RectCollider>
+    {
+        let gates = gates.iter().map(|g| g.active_collider());
+        self.colliders.iter().copied().chain(gates).chain(platforms.iter().map(|p| p.collider())).collect()
+    }
+
     // debug: prints out a list of the controller's current state
     /*pub fn debug(&mut self) {
         println!("Physics Controller status:");
-        println!("\tx: {}", self.x);
-        println!("\ty: {}", self.y);
-        println!("\tspeed: {}", self.speed);
-        println!("\tfall speed: {}", self.fall_speed);
+        println!("\tx: {}", self.x());
+        println!("\ty: {}", self.y());
+        println!("\tspeed: {}", self.speed());
+        println!("\tfall speed: {}", self.fall_speed());
         println!("\tjumps used: {}/{}", self.jumps_used, self.max_jumps);
         println!("\tmoving: {}", self.is_moving());
-        println!("\tgrounded: {}", self.is_grounded);
+        println!("\tgrounded: {}", self.is_grounded());
     }*/
 
     // accelerate_left: accelerates the character to the left
     pub fn accelerate_left(&mut self) {
         self.curr_direction = 0;
-        if self.speed > -self.max_speed {
-            self.speed -= self.acceleration;
+        let mut speed = self.speed();
+        if speed > -self.max_speed {
+            speed -= self.acceleration;
         }
-        if self.speed < -self.max_speed {
-            //self.speed = -self.max_speed;
-            self.speed += self.acceleration;
+        if speed < -self.max_speed {
+            //speed = -self.max_speed;
+            speed += self.acceleration;
         }
+        self.body.set_vx(speed);
     }
 
     // accelerate_right: accelerates the character to the right
     pub fn accelerate_right(&mut self) {
         self.curr_direction = 1;
-        if self.speed < self.max_speed {
-            //self.speed = self.acceleration;
-            self.speed += self.acceleration;
+        let mut speed = self.speed();
+        if speed < self.max_speed {
+            //speed = self.acceleration;
+            speed += self.acceleration;
         }
-        if self.speed > self.max_speed {
-            //self.speed = self.max_speed;
-            self.speed -= self.acceleration;
+        if speed > self.max_speed {
+            //speed = self.max_speed;
+            speed -= self.acceleration;
         }
+        self.body.set_vx(speed);
     }
 
     // update: manage the character's state each frame
-    pub fn update(&mut self, platecon: PlateController) {
+    pub fn update(&mut self, time: u128, gates: &[GateController], platforms: &[PlatformController]) {
         //maybe we don't want the character to move (like finishing a level)
         if self.can_move {
-            //move the character if necessary
-            let mut x_valid = true;
-            let mut y_valid = true;
-            let my_collider_x = RectCollider::new(self.x+self.speed, self.y, 69.0, 98.0);
-            let my_collider_y = RectCollider::new(self.x, self.y+self.fall_speed, 69.0, 98.0);
-            self.is_grounded = false;
-            for c in &self.colliders {
-                if c.is_touching(&my_collider_x) {
-                    x_valid = false;
-                }
-                if c.is_touching(&my_collider_y) {
-                    y_valid = false;
-                    if my_collider_y.y() < c.y() {
-                        self.y = c.y() - 100.0;
-                    } else {
-                        self.fall_speed = 0.0;
-                    }
-                }
-                if c.contains_point(self.x+50.0, self.y+105.0) {
-                    if self.fall_speed < 0.0 {
-                        y_valid = true;
-                    }
-                    self.is_grounded = true;
-                }
-            }
-            // are we hitting a closed gate?
-            if platecon.active_gate_collider().is_touching(&my_collider_x) {
-                x_valid = false;
-            }
-            if platecon.active_gate_collider().is_touching(&my_collider_y) {
-                y_valid = false;
-                if my_collider_y.y() < platecon.active_gate_collider().y() {
-                    self.y = platecon.active_gate_collider().y() - 100.0;
-                } else {
-                    self.fall_speed = 0.0;
+            // ride along with any platform we're standing on
+            for p in platforms {
+                if p.carries(&self.body.collider()) {
+                    self.body.shift(p.moved().0, p.moved().1);
                 }
             }
-            if platecon.active_gate_collider().contains_point(self.x+50.0, self.y+105.0) {
-                if self.fall_speed < 0.0 {
-                    y_valid = true;
+            let solids = self.solids(gates, platforms);
+            let me = self.body.collider();
+            let speed = self.speed();
+
+            // move across first, stopping flush against the first thing in the way
+            if self.body.move_x(&solids, self.level_width).is_some() && self.on_slope {
+                // walking off the top of a slope, step up onto the ground in front of us instead of walking into it
+                let step_up = solids.iter()
+                    .filter(|s| me.sweep(speed, 0.0, s).is_some())
+                    .map(|s| s.y() - me.height())
+                    .fold(me.y(), f32::min);
+                let stepped = self.body.at(me.x() + speed, step_up);
+                if me.y() - step_up <= STEP_HEIGHT && !solids.iter().any(|c| c.is_touching(&stepped)) {
+                    self.body.set_x(me.x() + speed);
+                    self.body.set_y(step_up);
+                    self.body.keep_in(self.level_width);
+                    self.body.set_vx(speed);
                 }
-                self.is_grounded = true;
-            }
-            // check if x and y are valid
-            if x_valid {
-                self.x = (self.x + self.speed).clamp(0.0, 1211.0);  // replace 1200.0 later with (CAM_W - TILE_SIZE) vars
             }
-            if y_valid {
-                self.y += self.fall_speed;
+            // then up or down from wherever that left us, and out of anything we ended up inside
+            self.body.move_y(&solids);
+            self.body.push_out(&solids);
+            if self.fall_speed() >= 0.0 && self.bodies.iter().any(|b| self.body.stands_on(b)) {
+                self.body.set_grounded(true);
             }
+            self.collide_with_slopes(me.x(), me.y());
 
-            // decelerate the character
-            if self.speed > 0.0 {
-                self.speed -= self.stop_speed;
-                if self.speed < 0.0 { self.speed = 0.0; }
-            } else if self.speed < 0.0 {
-                self.speed += self.stop_speed;
-                if self.speed > 0.0 { self.speed = 0.0; }
-            }
-
-            //simulate gravity
-            if self.fall_speed < self.max_fall_speed {
-                self.fall_speed += self.gravity;
-            }
+            self.body.slow_down();
+            self.body.fall(self.gravity, self.max_fall_speed);
 
-            if !self.is_grounded {
-                self.last_ground_time = SystemTime::now();
+            if !self.is_grounded() {
+                self.last_ground_time = time;
             }
 
             //reset jumps if we're on the ground and we've been on the ground for a little while
-            if self.is_grounded && self.fall_speed > 0.0 && self.last_ground_time+Duration::from_millis(100) < SystemTime::now() {
+            if self.is_grounded() && self.fall_speed() > 0.0 && self.last_ground_time + 100 < time {
                 self.reset_jumps();
-                self.fall_speed = 0.0;
+                self.body.set_vy(0.0);
+            }
+        }
+    }
+
+    // collide_with_slopes: stands us on any slope we walked or fell into, so we can walk up them and slide down them.
+    // the tall side and the bottom of a slope are walls, so bumping into those puts us back where we were
+    fn collide_with_slopes(&mut self, old_x: f32, old_y: f32) {
+        // if we were on the ground, stay stuck to the slope on the way down instead of bouncing off it
+        let stick = (self.on_slope || self.is_grounded()) && self.fall_speed() >= 0.0;
+        self.on_slope = false;
+        let (width, height) = (self.body.width(), self.body.height());
+        for s in &self.slopes {
+            let (x, y) = (self.body.x(), self.body.y());
+            let feet = RectCollider::new(x + (width - FEET_WIDTH)/2.0, y, FEET_WIDTH, height);
+            let feet_y = y + height;
+            if s.is_below(&feet) {
+                let surface = s.resting_y(&feet);
+                // how far into the slope we could have gone this frame
+                let reach = self.body.vx().abs() * s.steepness() + self.body.vy().abs() + 2.0;
+                let above = if stick { reach } else { 0.0 };
+                if self.body.vy() >= 0.0 && feet_y >= surface - above && feet_y <= surface + reach {
+                    self.body.set_y(surface - height);
+                    self.body.set_vy(0.0);
+                    self.body.set_grounded(true);
+                    self.on_slope = true;
+                    // gravity pulls us down the slope, more the steeper it is
+                    let sine = s.steepness() / (1.0 + s.steepness().powf(2.0)).powf(0.5);
+                    self.body.set_vx(self.body.vx() + s.downhill() * SLIDE_FACTOR * self.gravity * sine);
+                    continue;
+                }
+            }
+            let body = |x: f32, y: f32| RectCollider::new(x, y, width, height - STEP_HEIGHT);
+            if s.is_touching(&body(x, y)) {
+                if s.is_touching(&body(x, old_y)) {
+                    self.body.set_x(old_x);
+                    self.body.set_vx(0.0);
+                }
+                if s.is_touching(&body(self.body.x(), y)) {
+                    self.body.set_y(old_y);
+                    self.body.set_vy(0.0);
+                }
             }
         }
     }
 
     //jump: if we have jumps left, give ourselves a boost upwards. this is so we can support multiple jumps if we need
-    pub fn jump(&mut self) {
+    pub fn jump(&mut self, time: u128) {
         // if we have one jump, we have to use it on the ground
-        if self.jumps_used == 0 && self.jumps_used+1 == self.max_jumps && !self.is_grounded { return; }
+        if self.jumps_used == 0 && self.jumps_used+1 == self.max_jumps && !self.is_grounded() { return; }
         // the time comparison here is to prevent jumps from occurring on successive frames, which would be frustrating to players
-        if self.last_jump_time+Duration::from_millis(250) < SystemTime::now() && self.jumps_used < self.max_jumps {
+        if self.last_jump_time + 250 < time && self.jumps_used < self.max_jumps {
             self.jumps_used += 1;
-            self.fall_speed = -self.jump_speed;
-            self.last_jump_time = SystemTime::now();
-            self.is_grounded = false;
+            self.body.set_vy(-self.jump_speed);
+            self.last_jump_time = time;
+            self.body.set_grounded(false);
         }
     }
 
     // gives the player the ability to dash in a derection depending on the acceleration of the player
     pub fn dash(&mut self, speed: f32, first_press: i8) {
+        if !self.can_dash { return; }
         self.gravity = 0.0;
-        self.fall_speed = 0.0;
+        self.body.set_vy(0.0);
         if first_press == 1 {
             self.pre_dash_speed = speed;
         }
 
         if self.curr_direction == 1 {
-            self.speed = 32.0;
+            self.body.set_vx(32.0);
         }
         else if self.curr_direction == 0 {
-            self.speed = -32.0;
+            self.body.set_vx(-32.0);
         }
     }
 
+    // dash_ready: whether it's been long enough since the last dash started (at dash_start) to dash again
+    pub fn dash_ready(&self, time: u128, dash_start: u128) -> bool { time >= dash_start + 6*self.dash_time }
+    // dash_over: whether a dash that started at dash_start has run its course
+    pub fn dash_over(&self, time: u128, dash_start: u128) -> bool { time >= dash_start + self.dash_time }
+
     pub fn stop_dash(&mut self) {
-        self.speed = self.pre_dash_speed;
+        self.body.set_vx(self.pre_dash_speed);
         self.gravity = 1.0;
     }
 }
@@ -259,9 +328,7 @@ impl Clone for PhysicsController {
         PhysicsController {
             start_x: self.start_x,
             start_y: self.start_y,
-            x: self.x,
-            y: self.y,
-            speed: self.speed,
+            body: self.body,
             max_speed: self.max_speed,
             acceleration: self.acceleration,
             jump_speed: self.jump_speed,
@@ -269,16 +336,113 @@ impl Clone for PhysicsController {
             last_jump_time: self.last_jump_time,
             last_ground_time: self.last_ground_time,
             max_jumps: self.max_jumps,
-            stop_speed: self.stop_speed,
-            fall_speed: self.fall_speed,
+            jump_limit: self.jump_limit,
+            can_dash: self.can_dash,
+            level_width: self.level_width,
             gravity: self.gravity,
             max_fall_speed: self.max_fall_speed,
-            is_grounded: self.is_grounded,
             can_move: self.can_move,
             dash_time: self.dash_time,
             pre_dash_speed: self.pre_dash_speed,
             curr_direction: self.curr_direction,
-            colliders: self.colliders()
+            colliders: self.colliders(),
+            slopes: self.slopes(),
+            bodies: self.bodies.clone(),
+            on_slope: self.on_slope
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::Clock;
+
+    // at: the game time after some number of ticks
+    fn at(ticks: u64) -> u128 {
+        let mut clock = Clock::default();
+        (0..ticks).for_each(|_| clock.tick());
+        clock.millis()
+    }
+
+    // the player the game starts with, standing on a floor
+    fn player() -> PhysicsController {
+        let floor = RectCollider::new(0.0, 598.0, 1280.0, 50.0);
+        PhysicsController::new(75.0, 500.0, 8.0, 0.7, 20.0, 2, 0.2, 1.0, 40.0, vec!(floor))
+    }
+
+    #[test]
+    fn jumps_are_debounced() {
+        let mut physics = player();
+        physics.reset_jumps();
+
+        // no jumping until 250ms have gone by
+        assert_eq!(at(15), 250);
+        physics.jump(at(15));
+        assert_eq!(physics.fall_speed(), 0.0);
+        physics.jump(at(16));
+        assert_eq!(physics.fall_speed(), -20.0);
+
+        // and then not again until 250ms after that one
+        physics.set_fall_speed(0.0);
+        assert_eq!(at(31), 516);
+        physics.jump(at(31));
+        assert_eq!(physics.fall_speed(), 0.0);
+        physics.jump(at(32));
+        assert_eq!(physics.fall_speed(), -20.0);
+    }
+
+    #[test]
+    fn jumps_come_back_after_100ms_on_the_ground() {
+        let mut physics = player();
+
+        // off the ground at 1000ms
+        physics.set_y(300.0);
+        physics.update(at(60), &[], &[]);
+        assert!(!physics.is_grounded());
+
+        // land, and stand there
+        physics.set_y(500.0);
+        physics.set_fall_speed(0.0);
+        for tick in 61..=66 {
+            physics.update(at(tick), &[], &[]);
+            assert!(physics.is_grounded());
+            physics.jump(at(tick));
+            assert_eq!(physics.fall_speed(), 1.0);
+        }
+        assert_eq!(at(66), 1100);
+
+        physics.update(at(67), &[], &[]);
+        assert_eq!(physics.fall_speed(), 0.0);
+        physics.jump(at(67));
+        assert_eq!(physics.fall_speed(), -20.0);
+    }
+
+    #[test]
+    fn dashes_last_100ms_and_wait_600ms() {
+        let physics = player();
+        let dash_start = at(60);
+
+        assert!(!physics.dash_over(at(65), dash_start));
+        assert_eq!(at(66), 1100);
+        assert!(physics.dash_over(at(66), dash_start));
+
+        assert!(!physics.dash_ready(at(95), dash_start));
+        assert_eq!(at(96), 1600);
+        assert!(physics.dash_ready(at(96), dash_start));
+    }
+
+    #[test]
+    fn stepping_up_stays_in_a_level_narrower_than_the_player() {
+        let step = RectCollider::new(72.0, 560.0, 64.0, 64.0);
+        let mut physics = PhysicsController::new(0.0, 440.0, 8.0, 0.7, 20.0, 2, 0.2, 1.0, 40.0, vec!(step));
+        physics.add_slope(SlopeCollider::new(0.0, 560.0, 256.0, 64.0, false));
+        physics.set_level_width(40);
+        // walking off the slope into the step steps up onto it
+        for time in 0..10 {
+            physics.set_speed(5.0);
+            physics.update(time, &[], &[]);
         }
+        assert_eq!(physics.x(), 0.0);
     }
 }
@@ -0,0 +1,101 @@
+//physics_world: steps everything the physics moves together, so the player, the blocks and the balls bump into each
+//other as well as into the level
+use crate::ball_controller::{self, BallController};
+use crate::gate_controller::GateController;
+use crate::object_controller::ObjectController;
+use crate::platform_controller::PlatformController;
+use crate::player::Player;
+use crate::rect_collider::RectCollider;
+use crate::remote_player::RemotePlayer;
+use crate::rigid_body::{self, RigidBody};
+
+// a ball has to be further into something than this to count as stuck in it, resting against it isn't enough
+const PINNED_DEPTH: f32 = 0.5;
+
+// step: one tick for the player, every block and every ball, then pushes apart any of them that ended up
+// overlapping. a block being carried goes wherever the player does, so it doesn't get pushed around
+pub fn step(time: u128, player: &mut Player, blocks: &mut [ObjectController], balls: &mut [BallController], remote_player: Option<RemotePlayer>, gates: &[GateController], platforms: &[PlatformController]) {
+    // the player can stand on the blocks and balls as well as the level
+    let bodies = blocks.iter().filter(|b| !b.carried).map(|b| b.collider()).chain(balls.iter().map(|b| b.bounding_rect()));
+    player.physics.set_bodies(bodies.collect());
+    player.update(time, gates, platforms);
+    for (i, block) in blocks.iter_mut().enumerate() {
+        // only the block the remote player is carrying should follow them
+        block.update(player, remote_player.filter(|r| r.block_data.3 == i as i32), platforms);
+    }
+    for ball in balls.iter_mut() {
+        ball.update(platforms);
+        // balls roll through portals
+        if let Some((middle, velocity)) = player.portal.pass_through(time, ball.bounding_rect(), ball.velocity()) {
+            ball.set_middle(middle.0, middle.1);
+            ball.set_velocity(velocity);
+        }
+    }
+
+    let solids = player.physics.solids(gates, platforms);
+    let mut free: Vec<&mut ObjectController> = blocks.iter_mut().filter(|b| !b.carried).collect();
+    for j in 0..free.len() {
+        let (before, after) = free.split_at_mut(j);
+        let block = after[0].body_mut();
+        rigid_body::collide(player.physics.body_mut(), block, &solids);
+        for other in before.iter_mut() {
+            rigid_body::collide(other.body_mut(), block, &solids);
+        }
+    }
+    for j in 0..balls.len() {
+        let (before, after) = balls.split_at_mut(j);
+        let ball = &mut after[0];
+        touch(ball, player.physics.body_mut(), &solids);
+        for block in free.iter_mut() {
+            touch(ball, block.body_mut(), &solids);
+        }
+        for other in before.iter_mut() {
+            roll_into(other, ball);
+        }
+    }
+    // the player might have been pushed, so their collider goes with them
+    player.collider.update(&player.physics);
+}
+
+// touch: a ball against one of the rigid bodies. the ball is light, so it's the one that gets pushed out of the way,
+// unless it's stuck against a wall and can't be
+fn touch(ball: &mut BallController, body: &mut RigidBody, solids: &[RectCollider]) {
+    let (normal, depth) = match ball.collider().rect_penetration(&body.collider()) {
+        Some(push) => push,
+        None => return,
+    };
+    let mut moved = ball.collider();
+    moved.shift(normal.0 * depth, normal.1 * depth);
+    if solids.iter().any(|c| moved.rect_penetration(c).is_some_and(|(_, d)| d > PINNED_DEPTH)) {
+        body.shift(-normal.0 * depth, -normal.1 * depth);
+        // pushed up off the top of the ball, so it's standing on it
+        if normal.1 > 0.5 {
+            body.set_grounded(true);
+        }
+    } else {
+        ball.shift(normal.0 * depth, normal.1 * depth);
+    }
+    let (bx, by) = ball.velocity();
+    let ball_speed = bx * normal.0 + by * normal.1;
+    let body_speed = body.vx() * normal.0 + body.vy() * normal.1;
+    let (ball_after, body_after) = rigid_body::exchange(ball_speed, body_speed, ball_controller::MASS, body.mass(), ball_controller::RESTITUTION, 1.0);
+    ball.set_velocity((bx + (ball_after - ball_speed) * normal.0, by + (ball_after - ball_speed) * normal.1));
+    body.set_vx(body.vx() + (body_after - body_speed) * normal.0);
+    body.set_vy(body.vy() + (body_after - body_speed) * normal.1);
+}
+
+// roll_into: two balls knocking into each other. they weigh the same, so each is pushed half the way out
+fn roll_into(a: &mut BallController, b: &mut BallController) {
+    let (normal, depth) = match a.collider().penetration(&b.collider()) {
+        Some(push) => push,
+        None => return,
+    };
+    a.shift(normal.0 * depth / 2.0, normal.1 * depth / 2.0);
+    b.shift(-normal.0 * depth / 2.0, -normal.1 * depth / 2.0);
+    let (av, bv) = (a.velocity(), b.velocity());
+    let a_speed = av.0 * normal.0 + av.1 * normal.1;
+    let b_speed = bv.0 * normal.0 + bv.1 * normal.1;
+    let (a_after, b_after) = rigid_body::exchange(a_speed, b_speed, ball_controller::MASS, ball_controller::MASS, ball_controller::RESTITUTION, 1.0);
+    a.set_velocity((av.0 + (a_after - a_speed) * normal.0, av.1 + (a_after - a_speed) * normal.1));
+    b.set_velocity((bv.0 + (b_after - b_speed) * normal.0, bv.1 + (b_after - b_speed) * normal.1));
+}
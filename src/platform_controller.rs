@@ -0,0 +1,76 @@
+use crate::logic_controller::LogicController;
+use crate::rect_collider::RectCollider;
+
+// PlatformController: a surface that moves along a path of waypoints, looping back to the start once it
+// reaches the last one. it only moves while its wire is on
+#[derive(Clone)]
+pub struct PlatformController {
+    collider: RectCollider,
+    path: Vec<(f32, f32)>,
+    next_waypoint: usize,
+    // how far the platform goes each tick, in pixels
+    speed: f32,
+    wire: u32,
+    portal: bool,
+    moved: (f32, f32)
+}
+
+impl PlatformController {
+    pub fn new(_collider: RectCollider, _waypoints: Vec<(f32, f32)>, _speed: f32, _wire: u32, _portal: bool)
+        -> PlatformController
+    {
+        // the platform starts out at the start of its path
+        let mut path = vec!((_collider.x(), _collider.y()));
+        path.extend(_waypoints);
+        PlatformController {
+            collider: _collider,
+            path,
+            next_waypoint: 1,
+            speed: _speed,
+            wire: _wire,
+            portal: _portal,
+            moved: (0.0, 0.0)
+        }
+    }
+    pub fn collider(&self) -> RectCollider { self.collider }
+    pub fn portal(&self) -> bool { self.portal }
+    // moved: how far the platform went this tick
+    pub fn moved(&self) -> (f32, f32) { self.moved }
+
+    // update: moves the platform along its path, if its wire is on
+    pub fn update(&mut self, logic: &LogicController) {
+        self.moved = (0.0, 0.0);
+        if !logic.is_on(self.wire) || self.path.len() < 2 {
+            return;
+        }
+        let (start_x, start_y) = (self.collider.x(), self.collider.y());
+        let mut distance_left = self.speed;
+        // a fast platform can go past more than one waypoint in a frame, but it shouldn't go round and round forever
+        for _ in 0..self.path.len() {
+            let target = self.path[self.next_waypoint];
+            let dx = target.0 - self.collider.x();
+            let dy = target.1 - self.collider.y();
+            let distance = (dx.powf(2.0) + dy.powf(2.0)).powf(0.5);
+            if distance > distance_left {
+                self.collider.set_x(self.collider.x() + dx/distance*distance_left);
+                self.collider.set_y(self.collider.y() + dy/distance*distance_left);
+                break;
+            }
+            self.collider.set_x(target.0);
+            self.collider.set_y(target.1);
+            distance_left -= distance;
+            self.next_waypoint = (self.next_waypoint + 1) % self.path.len();
+        }
+        self.moved = (self.collider.x() - start_x, self.collider.y() - start_y);
+    }
+
+    // carries: whether something was standing on the platform (or got hit by it) this tick, and should move along with it
+    pub fn carries(&self, other: &RectCollider) -> bool {
+        if self.moved == (0.0, 0.0) {
+            return false;
+        }
+        let before = RectCollider::new(self.collider.x() - self.moved.0, self.collider.y() - self.moved.1, self.collider.width(), self.collider.height());
+        let feet = RectCollider::new(other.x(), other.y() + other.height(), other.width(), 6.0);
+        before.is_touching(&feet) || self.collider.is_touching(other)
+    }
+}
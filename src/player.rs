@@ -1,8 +1,11 @@
 use crate::animation_controller::AnimController;
+use crate::levels::Rule;
 use crate::physics_controller::PhysicsController;
 use crate::portal_controller::PortalController;
 use crate::rect_collider::RectCollider;
-use crate::plate_controller::PlateController;
+use crate::gate_controller::GateController;
+use crate::platform_controller::PlatformController;
+use crate::slope_collider::SlopeCollider;
 
 pub struct Player {
     pub physics: PhysicsController,
@@ -10,32 +13,44 @@ pub struct Player {
     pub temp_collider: RectCollider,
     pub anim: AnimController,
     pub portal: PortalController,
+    // the emancipation grids in the level, which the player walks through rather than into
+    grids: Vec<RectCollider>,
     dead: bool,
     pub flip_horizontal: bool,
 }
 
 impl Player {
-    pub fn new(_physics: PhysicsController, _collider: RectCollider, _anim: AnimController, _portal: PortalController)
+    pub fn new(mut _physics: PhysicsController, _collider: RectCollider, _anim: AnimController, _portal: PortalController)
                -> Player
     {
+        // the physics moves a body the size of the collider
+        _physics.set_size(_collider.width(), _collider.height());
         Player {
             physics: _physics,
             collider: _collider,
             temp_collider: _collider,
             anim: _anim,
             portal: _portal,
+            grids: vec!(),
             dead: false,
             flip_horizontal: false,
         }
     }
     pub fn is_dead(&self) -> bool { self.dead }
+    pub fn grids(&self) -> &[RectCollider] { &self.grids }
+    pub fn in_grid(&self) -> bool { self.grids.iter().any(|g| self.collider.is_touching(g)) }
 
     // update: handle all the updates we need
-    pub fn update(&mut self, platecon: PlateController) {
-        self.physics.update(platecon);
+    pub fn update(&mut self, time: u128, gates: &[GateController], platforms: &[PlatformController]) {
+        self.physics.update(time, gates, platforms);
         self.collider.update(&self.physics.clone());
         self.anim.update(self.physics.clone());
+        self.portal.follow_platforms(platforms);
         self.portal.update(self.physics.clone());
+        // going through a grid closes both portals
+        if self.in_grid() {
+            self.portal.close_all();
+        }
     }
 
     // stop: freeze the player in place
@@ -52,6 +67,11 @@ impl Player {
     }
 
     pub fn add_collider(&mut self, collider: RectCollider, block_type: &str) {
+        // grids don't stop the player or portal shots
+        if block_type == "grid" {
+            self.grids.push(collider);
+            return;
+        }
         self.physics.add_collider(collider);
         if block_type == "portalblock" {
             self.portal.add_valid_surface(collider);
@@ -60,8 +80,27 @@ impl Player {
         }
     }
 
+    // restrict: only lets the player do what the level allows
+    pub fn restrict(&mut self, rules: &[Rule]) {
+        self.physics.restrict(rules);
+        self.portal.restrict(rules);
+    }
+
+    // set_level_size: the player can't walk, shoot or throw past the edges of the level
+    pub fn set_level_size(&mut self, width: u32, height: u32) {
+        self.physics.set_level_width(width);
+        self.portal.set_level_size(width, height);
+    }
+
+    // add_slope: slopes can be walked on and take portals
+    pub fn add_slope(&mut self, slope: SlopeCollider) {
+        self.physics.add_slope(slope);
+        self.portal.add_slope(slope);
+    }
+
     pub fn reset_colliders(&mut self) {
         self.physics.reset_colliders();
+        self.grids.clear();
         self.portal.reset_surfaces();
         self.portal.close_all();
     }
@@ -1,7 +1,8 @@
-use std::time::{Duration, SystemTime};
-
+use crate::levels::{Rule, WINDOW_HEIGHT, WINDOW_WIDTH};
 use crate::physics_controller::PhysicsController;
+use crate::platform_controller::PlatformController;
 use crate::rect_collider::RectCollider;
+use crate::slope_collider::SlopeCollider;
 
 pub struct PortalController {
     wand_x: i32,
@@ -14,10 +15,19 @@ pub struct PortalController {
     should_rotate: bool,
     physics: PhysicsController,
     last_portal_used: i8,
-    last_portal_time: SystemTime,
-    last_teleport_time: SystemTime,
+    // game times (see Clock), in ms
+    last_portal_time: u128,
+    last_teleport_time: u128,
     valid_portal_surfaces: Vec<RectCollider>,
     invalid_portal_surfaces: Vec<RectCollider>,
+    // slopes always take portals
+    slopes: Vec<SlopeCollider>,
+    // where the moving platforms are this tick
+    platforms: Vec<PlatformController>,
+    // what the level won't let us do
+    rules: Vec<Rule>,
+    // how big the level is, nothing we shoot or throw goes past its edges
+    level_size: (f32, f32),
     has_teleported_blue: i32,
     has_teleported_orange: i32
 }
@@ -37,10 +47,14 @@ impl PortalController {
             should_rotate: true,
             physics: _physics,
             last_portal_used: 0,
-            last_portal_time: SystemTime::now(),
-            last_teleport_time: SystemTime::now(),
+            last_portal_time: 0,
+            last_teleport_time: 0,
             valid_portal_surfaces: _surfaces,
             invalid_portal_surfaces: _inval_surfaces,
+            slopes: vec!(),
+            platforms: vec!(),
+            rules: vec!(),
+            level_size: (WINDOW_WIDTH as f32, WINDOW_HEIGHT as f32),
             has_teleported_blue: 0,
             has_teleported_orange: 0
         }
@@ -52,6 +66,23 @@ impl PortalController {
     pub fn potion_y(&self) -> i32 { self.potion_y }
     pub fn rotation(&self) -> f32 { self.wand_rotation }
     pub fn last_portal(&self) -> i8 { self.last_portal_used }
+    pub fn can_shoot(&self) -> bool { !self.rules.contains(&Rule::PotionsOnly) }
+    pub fn can_throw(&self) -> bool { !self.rules.contains(&Rule::WandOnly) }
+    // can_open: whether the level lets us make the portal of this color (0 is blue, 1 is orange)
+    pub fn can_open(&self, index: usize) -> bool {
+        !self.rules.contains(if index == 0 { &Rule::OrangeOnly } else { &Rule::BlueOnly })
+    }
+
+    pub fn restrict(&mut self, rules: &[Rule]) {
+        self.rules = rules.to_vec();
+    }
+
+    pub fn set_level_size(&mut self, width: u32, height: u32) {
+        self.level_size = (width as f32, height as f32);
+        for potion in self.potions.iter_mut() {
+            potion.set_bounds(self.level_size);
+        }
+    }
 
     pub fn add_valid_surface(&mut self, new_collider: RectCollider) {
         self.valid_portal_surfaces.push(new_collider);
@@ -61,9 +92,14 @@ impl PortalController {
         self.invalid_portal_surfaces.push(new_collider);
     }
 
+    pub fn add_slope(&mut self, new_slope: SlopeCollider) {
+        self.slopes.push(new_slope);
+    }
+
     pub fn reset_surfaces(&mut self) {
         self.valid_portal_surfaces = vec!();
         self.invalid_portal_surfaces = vec!();
+        self.slopes = vec!();
     }
 
     pub fn all_colliders(&self) -> Vec<RectCollider> {
@@ -77,6 +113,27 @@ impl PortalController {
         return_vec
     }
 
+    // platform_colliders: the moving platforms, which can be hit by the wand and potions like any other surface
+    fn platform_colliders(&self) -> Vec<RectCollider> {
+        self.platforms.iter().map(|p| p.collider()).collect()
+    }
+
+    // platform_at: the moving platform a portal opened at this point would be on, if any
+    fn platform_at(&self, point: (f32, f32)) -> Option<usize> {
+        let spot = RectCollider::new(point.0-2.5, point.1-2.5, 5.0, 5.0);
+        self.platforms.iter().position(|p| p.collider().is_touching(&spot))
+    }
+
+    // follow_platforms: catches up with the moving platforms, taking any portals on them along for the ride
+    pub fn follow_platforms(&mut self, platforms: &[PlatformController]) {
+        self.platforms = platforms.to_vec();
+        for portal in self.portals.iter_mut() {
+            if let Some(platform) = portal.platform().and_then(|i| platforms.get(i)) {
+                portal.shift(platform.moved().0, platform.moved().1);
+            }
+        }
+    }
+
     // get_potion_state: returns a representation of where the potions are
     pub fn get_potion_state(&self) -> (Option<(f32, f32, f64)>, Option<(f32, f32, f64)>) {
         let mut state1: Option<(f32, f32, f64)> = None;
@@ -97,8 +154,9 @@ impl PortalController {
     // update the physics controllers so the wand can rotate properly
     pub fn update(&mut self, newphysics: PhysicsController) {
         self.physics = newphysics;
-        let potion0state = self.potions[0].update();
-        let potion1state = self.potions[1].update();
+        let platforms = self.platform_colliders();
+        let potion0state = self.potions[0].update(&platforms);
+        let potion1state = self.potions[1].update(&platforms);
         if potion0state.is_some() {
             let p0s = potion0state.unwrap();
             self.open_portal_at_point(0, (p0s.0, p0s.1), p0s.2);
@@ -110,7 +168,7 @@ impl PortalController {
     }
 
     // teleport: teleports the player to a specific portal (UNFINISHED)
-    pub fn teleport(&mut self, player_collider: &mut RectCollider, player_physics: &mut PhysicsController) -> bool {
+    pub fn teleport(&mut self, time: u128, player_collider: &mut RectCollider, player_physics: &mut PhysicsController) -> bool {
         let mut can_teleport = true;
         for p in &self.portals {
             if !p.is_open() { can_teleport = false; }
@@ -118,14 +176,20 @@ impl PortalController {
         if can_teleport {
             for i in 0..self.portals.len() {
                 // we can only use each portal once every 100ms
-                if self.portals[i].last_used()+Duration::from_millis(500) < SystemTime::now() && player_collider.is_touching(&self.portals[i].collider()) {
+                if self.portals[i].last_used() + 500 < time && player_collider.is_touching(&self.portals[i].collider()) {
                     let in_portal = i;
                     let out_portal = (i+1)%self.portals.len();
                     let in_dir = self.portals[in_portal].rotation+180.0;
                     let out_dir = (self.portals[out_portal].rotation+360.0) % 360.0;
                     let change_direction = (out_dir - in_dir + 360.0) % 360.0;
+                    // exiting on a slope: come out a little way along the way the portal faces
+                    if out_dir % 90.0 != 0.0 {
+                        let facing = out_dir/57.29;
+                        player_physics.set_x(self.portals[out_portal].x()+30.0+facing.cos()*60.0-34.5);
+                        player_physics.set_y(self.portals[out_portal].y()+50.0+facing.sin()*80.0-49.0);
+                    }
                     // exiting on a left wall
-                    if out_dir == 0.0 {
+                    else if out_dir == 0.0 {
                         player_physics.set_x(self.portals[out_portal].x()+30.0);
                         player_physics.set_y(self.portals[out_portal].y()+5.0);
                     }
@@ -145,7 +209,12 @@ impl PortalController {
                         player_physics.set_y(self.portals[out_portal].y()-90.0);
                     }
                     // conserve momentum
-                    if change_direction == 90.0 || change_direction == 270.0 {
+                    if in_dir % 90.0 != 0.0 || out_dir % 90.0 != 0.0 {
+                        // slopes aren't a quarter turn from anything, so just keep our speed and point it out of the portal
+                        let total = (player_physics.speed().powf(2.0) + player_physics.fall_speed().powf(2.0)).powf(0.5);
+                        player_physics.set_speed(total*(out_dir/57.29).cos());
+                        player_physics.set_fall_speed(total*(out_dir/57.29).sin());
+                    } else if change_direction == 90.0 || change_direction == 270.0 {
                         let old_speed = player_physics.speed();
                         if out_dir == 0.0 {
                             player_physics.set_speed(player_physics.fall_speed());
@@ -167,8 +236,8 @@ impl PortalController {
                             player_physics.set_fall_speed(-player_physics.fall_speed());
                         }
                     }
-                    self.last_teleport_time = SystemTime::now();
-                    let _ = &self.portals[out_portal].reset_last_used();
+                    self.last_teleport_time = time;
+                    self.portals[out_portal].reset_last_used(time);
                     return true;
                 }
             }
@@ -176,6 +245,24 @@ impl PortalController {
         return false;
     }
 
+    // pass_through: sends something touching one of the portals out of the other one, keeping its speed but pointing
+    // it out of the portal. gives back where its middle comes out, and how fast it's going
+    pub fn pass_through(&mut self, time: u128, collider: RectCollider, velocity: (f32, f32))
+        -> Option<((f32, f32), (f32, f32))>
+    {
+        if !self.portals.iter().all(|p| p.is_open()) { return None; }
+        let in_portal = self.portals.iter().position(|p| p.last_used() + 500 < time && collider.is_touching(&p.collider()))?;
+        let out_portal = (in_portal+1)%self.portals.len();
+        let facing = ((self.portals[out_portal].rotation+360.0) % 360.0)/57.29;
+        let exit = self.portals[out_portal].collider();
+        // far enough out that it doesn't come out inside the wall
+        let reach = collider.width().max(collider.height())/2.0 + 10.0;
+        let middle = (exit.x() + exit.width()/2.0 + facing.cos()*reach, exit.y() + exit.height()/2.0 + facing.sin()*reach);
+        let total = (velocity.0.powf(2.0) + velocity.1.powf(2.0)).powf(0.5);
+        self.portals[out_portal].reset_last_used(time);
+        Some((middle, (total*facing.cos(), total*facing.sin())))
+    }
+
     //next_rotation: returns a float indicating the angle of the next frame
     pub fn next_rotation(&mut self, mouse_x:i32, mouse_y: i32) -> f32 {
         if self.should_rotate {
@@ -189,19 +276,24 @@ impl PortalController {
     }
 
     // open_portal: figures out where a portal should go and opens it there
-    pub fn open_portal(&mut self, index: usize) -> i32 {
+    pub fn open_portal(&mut self, time: u128, index: usize) -> i32 {
+        if !self.can_shoot() || !self.can_open(index) {
+            return 0;
+        }
         // we can only open a portal every 100ms
-        if self.should_rotate && self.last_portal_time+Duration::from_millis(100) < SystemTime::now() {
+        if self.should_rotate && self.last_portal_time + 100 < time {
             // fire two raycasts: one to determine the point where we create the portal and one to determine the angle
-            let portal_point = Raycast::new(self.physics.x()+self.wand_x as f32+50.0, self.physics.y()+self.wand_y as f32+10.0, self.wand_rotation/57.29, self.all_colliders()).cast();
-            let rotation_point = Raycast::new(self.physics.x()+self.wand_x as f32+50.0, self.physics.y()+self.wand_y as f32+9.0, self.wand_rotation/57.29, self.all_colliders()).cast();
+            let mut surfaces = self.all_colliders();
+            surfaces.extend(self.platform_colliders());
+            let portal_point = Raycast::new(self.physics.x()+self.wand_x as f32+50.0, self.physics.y()+self.wand_y as f32+10.0, self.wand_rotation/57.29, surfaces.clone(), self.slopes.clone(), self.level_size).cast();
+            let rotation_point = Raycast::new(self.physics.x()+self.wand_x as f32+50.0, self.physics.y()+self.wand_y as f32+9.0, self.wand_rotation/57.29, surfaces, self.slopes.clone(), self.level_size).cast();
             if portal_point.is_some() && rotation_point.is_some() {
                 let pp = portal_point.unwrap();
                 let rp = rotation_point.unwrap();
                 //portals can't overlap
                 if ((pp.0 - (self.portals[1-index].x()+30.0)).powf(2.0) + (pp.1 - (self.portals[1-index].y()+50.0)).powf(2.0)).powf(0.5) < 130.0 {
                     self.last_portal_used = index as i8;
-                    self.last_portal_time = SystemTime::now();
+                    self.last_portal_time = time;
                     return 0;
                 }
                 //how should the portal be rotated?
@@ -225,12 +317,7 @@ impl PortalController {
                     }
                 } else {
                     //slope
-                    if self.wand_rotation > 90.0 && self.wand_rotation < 270.0 {
-                        (((rp.1-pp.1)/(rp.0-pp.0)) as f32).atan()*57.29+90.0
-                    }
-                    else {
-                        180.0+(((rp.1-pp.1)/(rp.0-pp.0)) as f32).atan()*57.29+90.0
-                    }
+                    slope_rotation(pp, rp, (self.wand_rotation/57.29).cos(), (self.wand_rotation/57.29).sin())
                 };
                 // we hit a surface, but is it valid?
                 for i in &self.invalid_portal_surfaces {
@@ -238,11 +325,16 @@ impl PortalController {
                         return -1;
                     }
                 }
+                let platform = self.platform_at(pp);
+                if platform.is_some_and(|i| !self.platforms[i].portal()) {
+                    return -1;
+                }
                 // open the portal
                 self.portals[index].open(pp.0 - 30.0, pp.1 - 50.0, rot);
+                self.portals[index].attach(platform);
             }
             self.last_portal_used = index as i8;
-            self.last_portal_time = SystemTime::now();
+            self.last_portal_time = time;
         }
         return 1;
     }
@@ -257,12 +349,20 @@ impl PortalController {
                 return false;
             }
         }
+        let platform = self.platform_at(point);
+        if platform.is_some_and(|i| !self.platforms[i].portal()) {
+            return false;
+        }
         self.portals[index].open(point.0-30.0, point.1-50.0, rot);
+        self.portals[index].attach(platform);
         return true;
     }
 
     // throw_potion: starts one potion on a trajectory
     pub fn throw_potion(&mut self, index: usize, mouse_x: i32, mouse_y: i32) {
+        if !self.can_throw() || !self.can_open(index) {
+            return;
+        }
         // start x, start y, inital x velocity, inital y velocity
         let coll = self.all_colliders();
         let sx = self.physics.x() as i32 + self.potion_x + 12;
@@ -272,7 +372,7 @@ impl PortalController {
         let throw_direction = self.next_rotation(mouse_x, mouse_y)/57.29;
         let ixv = throw_power*throw_direction.cos();
         let iyv = throw_power*throw_direction.sin();
-        self.potions[index].throw(sx as f32, sy as f32, ixv as f32, iyv as f32, coll);
+        self.potions[index].throw(sx as f32, sy as f32, ixv as f32, iyv as f32, coll, self.slopes.clone());
         self.last_portal_used = index as i8;
     }
 
@@ -290,7 +390,10 @@ pub struct Portal {
     y: f32,
     rotation: f32,
     collider: RectCollider,
-    last_used: SystemTime
+    // the game time (see Clock) the portal was last come out of, in ms
+    last_used: u128,
+    // the moving platform the portal is on, if it's on one
+    platform: Option<usize>
 }
 
 impl Portal {
@@ -303,7 +406,8 @@ impl Portal {
             y: -100.0,
             rotation: 0.0,
             collider: RectCollider::new(-100.0, -100.0, 50.0, 90.0),
-            last_used: SystemTime::now()
+            last_used: 0,
+            platform: None
         }
     }
 
@@ -312,9 +416,10 @@ impl Portal {
     pub fn y(&self) -> f32 { self.y }
     pub fn rotation(&self) -> f32 { self.rotation }
     pub fn collider(&self) -> RectCollider { self.collider }
-    pub fn last_used(&self) -> SystemTime { self.last_used }
+    pub fn last_used(&self) -> u128 { self.last_used }
+    pub fn platform(&self) -> Option<usize> { self.platform }
 
-    pub fn reset_last_used(&mut self) { self.last_used = SystemTime::now(); }
+    pub fn reset_last_used(&mut self, time: u128) { self.last_used = time; }
 
     /*pub fn set_x(&mut self, _x: f32) { self.x = _x; }
     pub fn set_y(&mut self, _y: f32) { self.y = _y; }
@@ -333,6 +438,18 @@ impl Portal {
         self.collider.set_y(new_y+if new_rot == 0.0 || new_rot == 180.0 {15.0} else {45.0});
         self.collider.set_width(if new_rot == 0.0 || new_rot == 180.0 {40.0} else {70.0});
         self.collider.set_height(if new_rot == 0.0 || new_rot == 180.0 {70.0} else {40.0});
+        self.platform = None;
+    }
+
+    // attach: sticks the portal to a moving platform, so it moves along with it
+    pub fn attach(&mut self, platform: Option<usize>) { self.platform = platform; }
+
+    // shift: moves an open portal (and its collider) along with the platform it's on
+    pub fn shift(&mut self, dx: f32, dy: f32) {
+        self.x += dx;
+        self.y += dy;
+        self.collider.set_x(self.collider.x() + dx);
+        self.collider.set_y(self.collider.y() + dy);
     }
 
     // close: closes a portal by moving it offscreen
@@ -340,6 +457,7 @@ impl Portal {
         self.x = -100.0;
         self.y = -100.0;
         self.rotation = 0.0;
+        self.platform = None;
     }
 }
 
@@ -347,18 +465,23 @@ pub struct Raycast {
     start_x: f32,
     start_y: f32,
     rotation: f32,
-    colliders: Vec<RectCollider>
+    colliders: Vec<RectCollider>,
+    slopes: Vec<SlopeCollider>,
+    // the width and height of the level, the ray gives up once it leaves
+    bounds: (f32, f32)
 }
 
 impl Raycast {
-    pub fn new(_x: f32, _y: f32, _rot: f32, _colliders: Vec<RectCollider>)
+    pub fn new(_x: f32, _y: f32, _rot: f32, _colliders: Vec<RectCollider>, _slopes: Vec<SlopeCollider>, _bounds: (f32, f32))
         -> Raycast
     {
         Raycast {
             start_x: _x,
             start_y: _y,
             rotation: _rot,
-            colliders: _colliders
+            colliders: _colliders,
+            slopes: _slopes,
+            bounds: _bounds
         }
     }
 
@@ -368,7 +491,7 @@ impl Raycast {
         let mut curr_y = self.start_y;
         let mut has_hit = false;
         let allowed_offset = 5.0;
-        while !has_hit && curr_x > 0.0 && curr_x < 1220.0 && curr_y > -30.0 && curr_y < 660.0 {
+        while !has_hit && curr_x > 0.0 && curr_x < self.bounds.0-60.0 && curr_y > -30.0 && curr_y < self.bounds.1-60.0 {
             curr_x += self.rotation.cos();
             curr_y += self.rotation.sin();
             for c in &self.colliders {
@@ -397,6 +520,14 @@ impl Raycast {
                 }
                 if has_hit { break; }
             }
+            // a slope is hit where the ray crosses into it, which puts the point right on the slope
+            if !has_hit {
+                if let Some(point) = slope_hit(&self.slopes, curr_x - self.rotation.cos(), curr_y - self.rotation.sin(), curr_x, curr_y) {
+                    has_hit = true;
+                    curr_x = point.0;
+                    curr_y = point.1;
+                }
+            }
         }
         if has_hit {
             Some((curr_x, curr_y))
@@ -430,7 +561,10 @@ pub struct Potion {
     trace_collided: bool,
     collision_point: (f32, f32),
     trace_point: (f32, f32),
-    colliders: Vec<RectCollider>
+    colliders: Vec<RectCollider>,
+    slopes: Vec<SlopeCollider>,
+    // the width and height of the level, the potion is gone once it leaves
+    bounds: (f32, f32)
 }
 
 impl Potion {
@@ -451,7 +585,9 @@ impl Potion {
             trace_collided: false,
             collision_point: (-100.0, -100.0),
             trace_point: (-100.0, -100.0),
-            colliders: vec!()
+            colliders: vec!(),
+            slopes: vec!(),
+            bounds: (WINDOW_WIDTH as f32, WINDOW_HEIGHT as f32)
         }
     }
     pub fn index(&self) -> i8 { self.index }
@@ -459,9 +595,10 @@ impl Potion {
     pub fn x(&self) -> f32 { self.x }
     pub fn y(&self) -> f32 { self.y }
     pub fn rotation(&self) -> f64 { self.rotation }
+    pub fn set_bounds(&mut self, _bounds: (f32, f32)) { self.bounds = _bounds; }
 
     // throw: starts the potion
-    pub fn throw(&mut self, _x: f32, _y: f32, _x_velocity: f32, _init_y_velocity: f32, _colliders: Vec<RectCollider>) {
+    pub fn throw(&mut self, _x: f32, _y: f32, _x_velocity: f32, _init_y_velocity: f32, _colliders: Vec<RectCollider>, _slopes: Vec<SlopeCollider>) {
         if !self.thrown {
             self.thrown = true;
             self.collided = false;
@@ -475,89 +612,53 @@ impl Potion {
             self.collision_point = (-100.0, -100.0);
             self.trace_point = (-100.0, -100.0);
             self.colliders = _colliders;
+            self.slopes = _slopes;
         }
     }
 
-    // update: updates the position and checks for collisions
-    pub fn update(&mut self)
+    // update: updates the position and checks for collisions, with the moving platforms where they are now
+    pub fn update(&mut self, platforms: &[RectCollider])
         -> Option<(f32, f32, f32)>
     {
         if self.thrown {
-            let allowed_offset = 10.0;
             self.y_velocity += 0.2;
             self.rotation += 5.0;
             //update the position
             if !self.collided {
                 self.x += self.x_velocity;
                 self.y += self.y_velocity;
-                for c in &self.colliders {
-                    if c.contains_point(self.x, self.y) && !self.collided {
+                if let Some(point) = rect_hit(self.colliders.iter().chain(platforms), self.x - self.x_velocity, self.y - self.y_velocity, self.x, self.y) {
+                    self.collided = true;
+                    self.x = point.0;
+                    self.y = point.1;
+                    self.collision_point = point;
+                }
+                if !self.collided {
+                    if let Some(point) = slope_hit(&self.slopes, self.x - self.x_velocity, self.y - self.y_velocity, self.x, self.y) {
                         self.collided = true;
-                        let mut pos_reset = false;
-                        if !pos_reset && self.x > c.x() && self.x < c.x()+allowed_offset {
-                            // we hit the left edge of the collider
-                            let old_pos = self.x;
-                            self.x = c.x();
-                            pos_reset = true;
-                        }
-                        if !pos_reset && self.x > c.x()+c.width()-allowed_offset && self.x < c.x()+c.width() {
-                            // we hit the right edge of the collider
-                            let old_pos = self.x;
-                            self.x = c.x()+c.width();
-                            pos_reset = true;
-                        }
-                        if !pos_reset && self.y > c.y() && self.y < c.y()+allowed_offset {
-                            // we hit the top edge of the collider
-                            let old_pos = self.y;
-                            self.y = c.y();
-                            pos_reset = true;
-                        }
-                        if !pos_reset && self.y > c.y()+c.height()-allowed_offset && self.y < c.y()+c.height() {
-                            // we hit the bottom edge of the collider
-                            let old_pos = self.y;
-                            self.y = c.y()+c.height();
-                            pos_reset = true;
-                        }
-                        self.collision_point = (self.x, self.y);
+                        self.x = point.0;
+                        self.y = point.1;
+                        self.collision_point = point;
                     }
-                    if self.collided { break; }
                 }
             }
             //update the trace (used for calculating rotation of portal when landing)
             if !self.trace_collided {
                 self.trace_x += self.x_velocity;
                 self.trace_y += self.y_velocity;
-                for c in &self.colliders {
-                    if c.contains_point(self.trace_x, self.trace_y) && !self.trace_collided {
+                if let Some(point) = rect_hit(self.colliders.iter().chain(platforms), self.trace_x - self.x_velocity, self.trace_y - self.y_velocity, self.trace_x, self.trace_y) {
+                    self.trace_collided = true;
+                    self.trace_x = point.0;
+                    self.trace_y = point.1;
+                    self.trace_point = point;
+                }
+                if !self.trace_collided {
+                    if let Some(point) = slope_hit(&self.slopes, self.trace_x - self.x_velocity, self.trace_y - self.y_velocity, self.trace_x, self.trace_y) {
                         self.trace_collided = true;
-                        let mut pos_reset = false;
-                        if !pos_reset && self.trace_x > c.x() && self.trace_x < c.x()+allowed_offset {
-                            // we hit the left edge of the collider
-                            let old_pos = self.trace_x;
-                            self.trace_x = c.x();
-                            pos_reset = true;
-                        }
-                        if !pos_reset && self.trace_x > c.x()+c.width()-allowed_offset && self.trace_x < c.x()+c.width() {
-                            // we hit the right edge of the collider
-                            let old_pos = self.trace_x;
-                            self.trace_x = c.x()+c.width();
-                            pos_reset = true;
-                        }
-                        if !pos_reset && self.trace_y > c.y() && self.trace_y < c.y()+allowed_offset {
-                            // we hit the top edge of the collider
-                            let old_pos = self.trace_y;
-                            self.trace_y = c.y();
-                            pos_reset = true;
-                        }
-                        if !pos_reset && self.trace_y > c.y()+c.height()-allowed_offset && self.trace_y < c.y()+c.height() {
-                            // we hit the bottom edge of the collider
-                            let old_pos = self.trace_y;
-                            self.trace_y = c.y()+c.height();
-                            pos_reset = true;
-                        }
-                        self.trace_point = (self.trace_x, self.trace_y);
+                        self.trace_x = point.0;
+                        self.trace_y = point.1;
+                        self.trace_point = point;
                     }
-                    if self.trace_collided { break; }
                 }
             }
             // have both the potion and trace point hit?
@@ -585,12 +686,7 @@ impl Potion {
                     }
                 } else {
                     //slope
-                    if self.x_velocity < 0.0 {
-                        (((rp.1-pp.1)/(rp.0-pp.0)) as f32).atan()*57.29+90.0
-                    }
-                    else {
-                        180.0+(((rp.1-pp.1)/(rp.0-pp.0)) as f32).atan()*57.29+90.0
-                    }
+                    slope_rotation(pp, rp, self.x_velocity, self.y_velocity)
                 };
                 self.thrown = false;
                 self.collided = false;
@@ -602,7 +698,7 @@ impl Potion {
                 self.y_velocity = 0.0;
                 return retval;
             } else {
-                if self.x < 0.0 || self.x > 1280.0 || self.y < 0.0 || self.y > 720.0 {
+                if self.x < 0.0 || self.x > self.bounds.0 || self.y < 0.0 || self.y > self.bounds.1 {
                     // the potion is out of bounds
                     self.thrown = false;
                     self.collided = false;
@@ -620,3 +716,85 @@ impl Potion {
         None
     }
 }
+
+// rect_hit: where a step from one point to the next first goes into any of the colliders, if it does. the whole
+// step is checked, so a fast potion can't go through a thin wall, and the point is put exactly on the edge it went
+// in through so the portal's rotation can be worked out from it. a step that starts inside a collider stops where
+// it is
+fn rect_hit<'a>(colliders: impl Iterator<Item = &'a RectCollider>, from_x: f32, from_y: f32, to_x: f32, to_y: f32) -> Option<(f32, f32)> {
+    let point = RectCollider::new(from_x, from_y, 0.0, 0.0);
+    let (dx, dy) = (to_x - from_x, to_y - from_y);
+    let mut first: Option<(f32, RectCollider)> = None;
+    for c in colliders {
+        if c.contains_point(from_x, from_y) {
+            return Some((from_x, from_y));
+        }
+        if let Some(t) = point.sweep(dx, dy, c) {
+            if first.is_none_or(|(hit, _)| t < hit) {
+                first = Some((t, *c));
+            }
+        }
+    }
+    let (t, c) = first?;
+    let (x, y) = (from_x + dx * t, from_y + dy * t);
+    // snap onto whichever edge the point is closest to, which is the one it went in through
+    let edges = [(x - c.x()).abs(), (c.x() + c.width() - x).abs(), (y - c.y()).abs(), (c.y() + c.height() - y).abs()];
+    let closest = edges.iter().cloned().fold(f32::INFINITY, f32::min);
+    Some(if closest == edges[0] {
+        (c.x(), y)
+    } else if closest == edges[1] {
+        (c.x() + c.width(), y)
+    } else if closest == edges[2] {
+        (x, c.y())
+    } else {
+        (x, c.y() + c.height())
+    })
+}
+
+// slope_hit: where a step from one point to the next first goes into a slope, if it does
+fn slope_hit(slopes: &[SlopeCollider], from_x: f32, from_y: f32, to_x: f32, to_y: f32) -> Option<(f32, f32)> {
+    slopes.iter()
+        .filter(|s| s.contains_point(to_x, to_y))
+        .find_map(|s| s.entry_point(from_x, from_y, to_x, to_y))
+}
+
+// slope_rotation: the rotation of a portal on a surface that runs through two points, facing back
+// towards whatever was travelling in the direction (dir_x, dir_y) when it hit
+fn slope_rotation(pp: (f32, f32), rp: (f32, f32), dir_x: f32, dir_y: f32) -> f32 {
+    let facing = (rp.1-pp.1).atan2(rp.0-pp.0) - std::f32::consts::FRAC_PI_2;
+    let facing = if facing.cos()*dir_x + facing.sin()*dir_y > 0.0 { facing + std::f32::consts::PI } else { facing };
+    (facing*57.29 + 360.0) % 360.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::Clock;
+
+    // at: the game time after some number of ticks
+    fn at(ticks: u64) -> u128 {
+        let mut clock = Clock::default();
+        (0..ticks).for_each(|_| clock.tick());
+        clock.millis()
+    }
+
+    #[test]
+    fn portals_cool_down_after_use() {
+        let physics = PhysicsController::new(75.0, 500.0, 8.0, 0.7, 20.0, 2, 0.2, 1.0, 40.0, vec!());
+        let mut portals = PortalController::new(-10, 60, 20, 65, physics, vec!(Portal::new(0), Portal::new(1)), vec!(), vec!(), vec!());
+        assert!(portals.open_portal_at_point(0, (200.0, 300.0), 0.0));
+        assert!(portals.open_portal_at_point(1, (800.0, 300.0), 180.0));
+        let at_blue = RectCollider::new(190.0, 280.0, 20.0, 20.0);
+        let at_orange = RectCollider::new(790.0, 280.0, 20.0, 20.0);
+
+        // neither portal works until 500ms in
+        assert_eq!(at(30), 500);
+        assert!(portals.pass_through(at(30), at_blue, (5.0, 0.0)).is_none());
+        assert!(portals.pass_through(at(31), at_blue, (5.0, 0.0)).is_some());
+
+        // coming out of orange at 516ms, so nothing goes back in it until 500ms after that
+        assert_eq!(at(61), 1016);
+        assert!(portals.pass_through(at(61), at_orange, (5.0, 0.0)).is_none());
+        assert!(portals.pass_through(at(62), at_orange, (5.0, 0.0)).is_some());
+    }
+}
@@ -47,6 +47,52 @@ impl RectCollider {
         (self.height + self.y > other.y()) && (self.y < other.y() + other.height()) && (self.x + self.width > other.x()) && (self.x < other.x() + other.width())
     }
 
+    // penetration: the shortest push that moves this collider back out of the other one, along whichever axis it's
+    // least far in, or None if they don't overlap
+    pub fn penetration(&self, other: &RectCollider)
+        -> Option<(f32, f32)>
+    {
+        if !self.is_touching(other) { return None; }
+        let left = other.x() - (self.x + self.width);
+        let right = other.x() + other.width() - self.x;
+        let up = other.y() - (self.y + self.height);
+        let down = other.y() + other.height() - self.y;
+        let dx = if right < -left { right } else { left };
+        let dy = if down < -up { down } else { up };
+        Some(if dx.abs() < dy.abs() { (dx, 0.0) } else { (0.0, dy) })
+    }
+
+    // sweep: how far along a move of (dx, dy) this collider gets before it runs into the other one, from 0 (not at
+    // all) to 1 (the whole way), or None if it doesn't run into it. checking the whole path instead of just where
+    // the move ends means nothing can go fast enough to skip through a thin wall. colliders that already overlap
+    // don't run into each other, and neither do ones that only slide along each other's edges
+    pub fn sweep(&self, dx: f32, dy: f32, other: &RectCollider)
+        -> Option<f32>
+    {
+        // when the move starts and stops overlapping the other collider along one axis
+        let axis = |pos: f32, size: f32, d: f32, other_pos: f32, other_size: f32| {
+            if d == 0.0 {
+                if pos + size > other_pos && pos < other_pos + other_size {
+                    Some((f32::NEG_INFINITY, f32::INFINITY))
+                } else {
+                    None
+                }
+            } else {
+                let a = (other_pos - (pos + size)) / d;
+                let b = (other_pos + other_size - pos) / d;
+                Some((a.min(b), a.max(b)))
+            }
+        };
+        let (x_entry, x_exit) = axis(self.x, self.width, dx, other.x(), other.width())?;
+        let (y_entry, y_exit) = axis(self.y, self.height, dy, other.y(), other.height())?;
+        let entry = x_entry.max(y_entry);
+        if entry >= x_exit.min(y_exit) || !(0.0..=1.0).contains(&entry) {
+            None
+        } else {
+            Some(entry)
+        }
+    }
+
     pub fn contains_point(&self, _x: f32, _y: f32)
         -> bool
     {
@@ -59,3 +105,71 @@ impl RectCollider {
         self.y = physics_controller.y();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x: f32, y: f32) -> RectCollider {
+        RectCollider::new(x, y, 50.0, 50.0)
+    }
+
+    #[test]
+    fn sweep_from_flush_hits_straight_away() {
+        assert_eq!(square(0.0, 0.0).sweep(5.0, 0.0, &square(50.0, 0.0)), Some(0.0));
+        assert_eq!(square(0.0, 0.0).sweep(0.0, 5.0, &square(0.0, 50.0)), Some(0.0));
+    }
+
+    #[test]
+    fn sweep_stops_partway() {
+        assert_eq!(square(0.0, 0.0).sweep(20.0, 0.0, &square(60.0, 0.0)), Some(0.5));
+        assert_eq!(square(0.0, 0.0).sweep(5.0, 0.0, &square(60.0, 0.0)), None);
+        assert_eq!(square(0.0, 0.0).sweep(-20.0, 0.0, &square(60.0, 0.0)), None);
+    }
+
+    #[test]
+    fn sweep_ignores_overlapping_colliders() {
+        assert_eq!(square(0.0, 0.0).sweep(5.0, 0.0, &square(40.0, 0.0)), None);
+        assert_eq!(square(0.0, 0.0).sweep(0.0, 5.0, &square(40.0, 0.0)), None);
+    }
+
+    #[test]
+    fn sweep_ignores_sliding_along_an_edge() {
+        let floor = RectCollider::new(0.0, 50.0, 500.0, 10.0);
+        assert_eq!(square(0.0, 0.0).sweep(100.0, 0.0, &floor), None);
+        assert_eq!(square(0.0, 0.0).sweep(-100.0, 0.0, &floor), None);
+        let wall = RectCollider::new(50.0, 0.0, 10.0, 500.0);
+        assert_eq!(square(0.0, 0.0).sweep(0.0, 100.0, &wall), None);
+    }
+
+    #[test]
+    fn sweep_catches_fast_moves_through_thin_walls() {
+        let wall = RectCollider::new(500.0, 0.0, 1.0, 50.0);
+        let t = square(0.0, 0.0).sweep(1000.0, 0.0, &wall).unwrap();
+        assert!((t - 0.45).abs() < 0.001);
+        // the end of the move is already out the other side
+        assert!(!square(1000.0, 0.0).is_touching(&wall));
+    }
+
+    #[test]
+    fn penetration_pushes_out_the_shortest_way() {
+        assert_eq!(square(0.0, 0.0).penetration(&square(45.0, 0.0)), Some((-5.0, 0.0)));
+        assert_eq!(square(0.0, 0.0).penetration(&square(-45.0, 0.0)), Some((5.0, 0.0)));
+        assert_eq!(square(0.0, 0.0).penetration(&square(0.0, 45.0)), Some((0.0, -5.0)));
+        assert_eq!(square(0.0, 0.0).penetration(&square(0.0, -45.0)), Some((0.0, 5.0)));
+        // a little over on both axes goes out the one it's least over on
+        assert_eq!(square(0.0, 0.0).penetration(&square(40.0, 45.0)), Some((0.0, -5.0)));
+        assert_eq!(square(0.0, 0.0).penetration(&square(-45.0, -40.0)), Some((5.0, 0.0)));
+    }
+
+    #[test]
+    fn penetration_ties_go_up_or_down() {
+        assert_eq!(square(0.0, 0.0).penetration(&square(40.0, 40.0)), Some((0.0, -10.0)));
+    }
+
+    #[test]
+    fn penetration_needs_an_overlap() {
+        assert_eq!(square(0.0, 0.0).penetration(&square(50.0, 0.0)), None);
+        assert_eq!(square(0.0, 0.0).penetration(&square(0.0, 60.0)), None);
+    }
+}
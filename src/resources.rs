@@ -0,0 +1,64 @@
+// Resources: finds the folder that holds assets/ and the levels, so the game can be run from anywhere
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+// set this to the game's folder to override where we look for assets and levels
+pub const ROOT_ENV_VAR: &str = "WARP_WIZARDS_ROOT";
+
+static ROOT: OnceLock<PathBuf> = OnceLock::new();
+
+// init: picks the resource root, checking (in order) the env var, the --root flag,
+// the folder the executable is in, and finally the working directory
+pub fn init(cli_root: Option<String>) {
+    ROOT.get_or_init(|| find_root(cli_root));
+}
+
+// root: the folder everything is loaded relative to
+pub fn root() -> &'static Path {
+    ROOT.get_or_init(|| find_root(None))
+}
+
+// path: turns a path like "assets/in_game/cursor/cursor.png" into one under the resource root
+pub fn path(relative: &str) -> PathBuf {
+    root().join(relative)
+}
+
+// level_path: where a level file lives. a packaged build keeps them in levels/,
+// the repo keeps them next to the code in src/levels/
+pub fn level_path(filename: &str) -> PathBuf {
+    let packaged = root().join("levels");
+    if packaged.is_dir() {
+        packaged.join(filename)
+    } else {
+        root().join("src").join("levels").join(filename)
+    }
+}
+
+fn find_root(cli_root: Option<String>) -> PathBuf {
+    let mut candidates: Vec<(&str, PathBuf)> = vec!();
+    if let Ok(dir) = env::var(ROOT_ENV_VAR) {
+        candidates.push((ROOT_ENV_VAR, PathBuf::from(dir)));
+    }
+    if let Some(dir) = cli_root {
+        candidates.push(("--root", PathBuf::from(dir)));
+    }
+    if let Some(dir) = env::current_exe().ok().and_then(|exe| exe.parent().map(|p| p.to_path_buf())) {
+        candidates.push(("executable folder", dir));
+    }
+    for (source, dir) in candidates {
+        if has_assets(&dir) {
+            return dir;
+        }
+        // only complain about the places somebody asked us to look
+        if source == ROOT_ENV_VAR || source == "--root" {
+            eprintln!("{} points at {}, which has no assets folder; ignoring it", source, dir.display());
+        }
+    }
+    // fall back to wherever we were launched from, which is the repo root for cargo run
+    env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
+}
+
+fn has_assets(dir: &Path) -> bool {
+    dir.join("assets").is_dir()
+}
@@ -0,0 +1,290 @@
+//RigidBody: a box pushed around by the physics. the player and the blocks are both one, so they fall, slide and
+//bump into things (and each other) the same way
+use crate::rect_collider::RectCollider;
+
+// how far under a body we look for something for it to stand on
+const GROUND_PROBE: f32 = 1.0;
+// bounces slower than this just stop, so things settle down instead of jittering on the floor forever
+const MIN_BOUNCE: f32 = 2.0;
+
+#[derive(Copy, Clone)]
+pub struct RigidBody {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    vx: f32,
+    vy: f32,
+    mass: f32,
+    // how much sideways speed the body loses each tick on the ground, and in the air
+    friction: f32,
+    drag: f32,
+    // how much of its speed the body keeps when it bounces off something, 0 stops it dead
+    restitution: f32,
+    grounded: bool,
+}
+
+impl RigidBody {
+    pub fn new(_collider: RectCollider, _mass: f32, _friction: f32, _restitution: f32)
+        -> RigidBody
+    {
+        RigidBody {
+            x: _collider.x(),
+            y: _collider.y(),
+            width: _collider.width(),
+            height: _collider.height(),
+            vx: 0.0,
+            vy: 0.0,
+            mass: _mass,
+            friction: _friction,
+            drag: 0.0,
+            restitution: _restitution,
+            grounded: false,
+        }
+    }
+
+    //getters
+    pub fn x(&self) -> f32 { self.x }
+    pub fn y(&self) -> f32 { self.y }
+    pub fn width(&self) -> f32 { self.width }
+    pub fn height(&self) -> f32 { self.height }
+    pub fn vx(&self) -> f32 { self.vx }
+    pub fn vy(&self) -> f32 { self.vy }
+    pub fn mass(&self) -> f32 { self.mass }
+    pub fn is_grounded(&self) -> bool { self.grounded }
+    pub fn collider(&self) -> RectCollider { self.at(self.x, self.y) }
+    // at: the body's collider if it were at (x, y)
+    pub fn at(&self, x: f32, y: f32) -> RectCollider { RectCollider::new(x, y, self.width, self.height) }
+
+    //setters
+    pub fn set_x(&mut self, _x: f32) { self.x = _x; }
+    pub fn set_y(&mut self, _y: f32) { self.y = _y; }
+    pub fn set_vx(&mut self, _vx: f32) { self.vx = _vx; }
+    pub fn set_vy(&mut self, _vy: f32) { self.vy = _vy; }
+    pub fn set_drag(&mut self, _drag: f32) { self.drag = _drag; }
+    pub fn set_grounded(&mut self, _grounded: bool) { self.grounded = _grounded; }
+    pub fn set_size(&mut self, _width: f32, _height: f32) {
+        self.width = _width;
+        self.height = _height;
+    }
+    // shift: moves the body without checking for anything in the way, like riding along on a platform
+    pub fn shift(&mut self, dx: f32, dy: f32) {
+        self.x += dx;
+        self.y += dy;
+    }
+
+    // step: one tick of moving, colliding and slowing down, for bodies that don't need to do anything in between
+    pub fn step(&mut self, solids: &[RectCollider], level_width: f32, gravity: f32, max_fall_speed: f32) {
+        self.move_x(solids, level_width);
+        self.move_y(solids);
+        self.push_out(solids);
+        self.slow_down();
+        self.fall(gravity, max_fall_speed);
+    }
+
+    // move_x: moves the body across by its speed, stopping flush against the first solid in the way (and bouncing
+    // off it, if the body bounces). gives back what it ran into
+    pub fn move_x(&mut self, solids: &[RectCollider], level_width: f32)
+        -> Option<RectCollider>
+    {
+        let hit = first_hit(&self.collider(), self.vx, 0.0, solids);
+        match hit {
+            Some(c) => {
+                self.x = if self.vx > 0.0 { c.x() - self.width } else { c.x() + c.width() };
+                self.vx = bounce(self.vx, self.restitution);
+            }
+            None => self.x += self.vx,
+        }
+        self.keep_in(level_width);
+        hit
+    }
+
+    // keep_in: stops the body going off either side of a level, or keeps it at the left if the level is too narrow
+    pub fn keep_in(&mut self, level_width: f32) {
+        self.x = self.x.clamp(0.0, (level_width - self.width).max(0.0));
+    }
+
+    // move_y: moves the body up or down by its fall speed, landing on (or bumping its head on) the first solid it
+    // reaches. gives back what it ran into
+    pub fn move_y(&mut self, solids: &[RectCollider])
+        -> Option<RectCollider>
+    {
+        let hit = first_hit(&self.collider(), 0.0, self.vy, solids);
+        match hit {
+            Some(c) => {
+                self.y = if self.vy > 0.0 { c.y() - self.height } else { c.y() + c.height() };
+                self.vy = bounce(self.vy, self.restitution);
+            }
+            None => self.y += self.vy,
+        }
+        hit
+    }
+
+    // push_out: anything the body still overlaps (a gate closing on it, a platform moving into it, a corner it
+    // clipped) pushes it back out the shortest way. then it's on the ground if there's something right under it
+    pub fn push_out(&mut self, solids: &[RectCollider]) {
+        for c in solids {
+            if let Some((dx, dy)) = self.collider().penetration(c) {
+                self.shift(dx, dy);
+                if dy > 0.0 && self.vy < 0.0 {
+                    self.vy = 0.0;
+                }
+            }
+        }
+        self.grounded = self.vy >= 0.0 && solids.iter().any(|c| self.stands_on(c));
+    }
+
+    // stands_on: whether the other collider is right under the body
+    pub fn stands_on(&self, other: &RectCollider) -> bool {
+        RectCollider::new(self.x, self.y + self.height, self.width, GROUND_PROBE).is_touching(other)
+    }
+
+    // slow_down: friction on the ground, drag in the air
+    pub fn slow_down(&mut self) {
+        let slowing = if self.grounded { self.friction } else { self.drag };
+        if self.vx > 0.0 {
+            self.vx = (self.vx - slowing).max(0.0);
+        } else if self.vx < 0.0 {
+            self.vx = (self.vx + slowing).min(0.0);
+        }
+    }
+
+    // fall: gravity, up to the fastest the body can fall
+    pub fn fall(&mut self, gravity: f32, max_fall_speed: f32) {
+        if self.vy < max_fall_speed {
+            self.vy += gravity;
+        }
+    }
+}
+
+// collide: pushes apart two bodies that overlap, the lighter one further, and trades their speeds along the way they
+// hit like a collision between two masses. a body that would be pushed into one of the solids stays put and the other
+// one is pushed all the way instead. gives back whether they were touching
+pub fn collide(a: &mut RigidBody, b: &mut RigidBody, solids: &[RectCollider]) -> bool {
+    let (dx, dy) = match a.collider().penetration(&b.collider()) {
+        Some(push) => push,
+        None => return false,
+    };
+    let mut a_share = b.mass / (a.mass + b.mass);
+    let pinned = |body: &RigidBody, share: f32, sign: f32| {
+        let moved = body.at(body.x + dx * share * sign, body.y + dy * share * sign);
+        solids.iter().any(|c| c.is_touching(&moved))
+    };
+    if pinned(b, 1.0 - a_share, -1.0) {
+        a_share = 1.0;
+    } else if pinned(a, a_share, 1.0) {
+        a_share = 0.0;
+    }
+    a.shift(dx * a_share, dy * a_share);
+    b.shift(-dx * (1.0 - a_share), -dy * (1.0 - a_share));
+    let restitution = a.restitution.min(b.restitution);
+    if dx != 0.0 {
+        let (va, vb) = exchange(a.vx, b.vx, a.mass, b.mass, restitution, dx.signum());
+        a.vx = va;
+        b.vx = vb;
+    } else {
+        let (va, vb) = exchange(a.vy, b.vy, a.mass, b.mass, restitution, dy.signum());
+        a.vy = va;
+        b.vy = vb;
+        // whichever one is on top is standing on the other
+        if dy < 0.0 { a.grounded = true; } else { b.grounded = true; }
+    }
+    true
+}
+
+// exchange: the speeds of two masses after they hit, along the direction a was pushed away from b. ones that are
+// already moving apart are left alone
+pub fn exchange(va: f32, vb: f32, ma: f32, mb: f32, restitution: f32, direction: f32) -> (f32, f32) {
+    let closing = (va - vb) * direction;
+    if closing >= 0.0 { return (va, vb); }
+    let impulse = -(1.0 + restitution) * (va - vb) / (1.0/ma + 1.0/mb);
+    (va + impulse/ma, vb - impulse/mb)
+}
+
+// bounce: the speed a body comes back off something at, or nothing if it's too slow to bother
+fn bounce(speed: f32, restitution: f32) -> f32 {
+    let back = -speed * restitution;
+    if back.abs() < MIN_BOUNCE { 0.0 } else { back }
+}
+
+// first_hit: the first of the solids a body runs into moving by (dx, dy), if it runs into any
+fn first_hit(body: &RectCollider, dx: f32, dy: f32, solids: &[RectCollider]) -> Option<RectCollider> {
+    let mut first: Option<(f32, RectCollider)> = None;
+    for c in solids {
+        if let Some(t) = body.sweep(dx, dy, c) {
+            if first.is_none_or(|(hit, _)| t < hit) {
+                first = Some((t, *c));
+            }
+        }
+    }
+    first.map(|(_, c)| c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn body(x: f32, mass: f32, restitution: f32) -> RigidBody {
+        RigidBody::new(RectCollider::new(x, 0.0, 50.0, 50.0), mass, 0.0, restitution)
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 0.001
+    }
+
+    #[test]
+    fn lighter_body_is_pushed_further() {
+        let mut a = body(0.0, 70.0, 0.0);
+        let mut b = body(40.0, 30.0, 0.0);
+        assert!(collide(&mut a, &mut b, &[]));
+        // 10 px of overlap, split 30:70
+        assert!(close(a.x(), -3.0));
+        assert!(close(b.x(), 47.0));
+        assert!(a.collider().penetration(&b.collider()).is_none());
+    }
+
+    #[test]
+    fn pinned_body_stays_put() {
+        let wall = RectCollider::new(90.0, -100.0, 10.0, 300.0);
+        let mut a = body(0.0, 70.0, 0.0);
+        let mut b = body(40.0, 30.0, 0.0);
+        assert!(collide(&mut a, &mut b, &[wall]));
+        assert!(close(a.x(), -10.0));
+        assert!(close(b.x(), 40.0));
+    }
+
+    #[test]
+    fn bodies_apart_dont_collide() {
+        let mut a = body(0.0, 70.0, 0.0);
+        let mut b = body(50.0, 30.0, 0.0);
+        assert!(!collide(&mut a, &mut b, &[]));
+        assert_eq!((a.x(), b.x()), (0.0, 50.0));
+    }
+
+    #[test]
+    fn collisions_keep_momentum() {
+        let mut a = body(0.0, 70.0, 0.5);
+        let mut b = body(40.0, 30.0, 0.5);
+        a.set_vx(5.0);
+        b.set_vx(-2.0);
+        assert!(collide(&mut a, &mut b, &[]));
+        assert!(close(70.0*a.vx() + 30.0*b.vx(), 70.0*5.0 + 30.0*-2.0));
+        // they come apart at half the speed they hit at
+        assert!(close(b.vx() - a.vx(), 3.5));
+    }
+
+    #[test]
+    fn exchange_leaves_separating_bodies_alone() {
+        assert_eq!(exchange(-1.0, 2.0, 70.0, 30.0, 0.5, -1.0), (-1.0, 2.0));
+        let (va, vb) = exchange(2.0, 0.0, 10.0, 10.0, 0.0, -1.0);
+        assert!(close(va, 1.0) && close(vb, 1.0));
+    }
+
+    #[test]
+    fn body_wider_than_the_level_stays_at_the_left() {
+        let mut a = body(10.0, 70.0, 0.0);
+        a.set_vx(5.0);
+        a.move_x(&[], 30.0);
+        assert_eq!(a.x(), 0.0);
+    }
+}
@@ -0,0 +1,103 @@
+use crate::rect_collider::RectCollider;
+
+// SlopeCollider: a right triangle that fills the bottom half of its box, cut by a slope from one corner to the other.
+// rises_right means the slope goes up from the bottom left corner to the top right one
+#[derive(Copy, Clone)]
+pub struct SlopeCollider {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    rises_right: bool
+}
+
+impl SlopeCollider {
+    pub fn new(_x: f32, _y: f32, _width: f32, _height: f32, _rises_right: bool)
+        -> SlopeCollider
+    {
+        SlopeCollider {
+            x: _x,
+            y: _y,
+            width: _width,
+            height: _height,
+            rises_right: _rises_right,
+        }
+    }
+
+    // steepness: how many pixels the slope goes up for every pixel across
+    pub fn steepness(&self) -> f32 { self.height / self.width }
+    pub fn bounding_rect(&self) -> RectCollider { RectCollider::new(self.x, self.y, self.width, self.height) }
+
+    // surface_y: the height of the slope at some x, if that x is over it
+    pub fn surface_y(&self, _x: f32) -> Option<f32> {
+        if _x < self.x || _x > self.x + self.width {
+            return None;
+        }
+        let across = if self.rises_right { _x - self.x } else { self.x + self.width - _x };
+        Some(self.y + self.height - across * self.steepness())
+    }
+
+    // is_below: whether any of the slope is straight under (or over) a rectangle
+    pub fn is_below(&self, other: &RectCollider) -> bool {
+        other.x() < self.x + self.width && other.x() + other.width() > self.x
+    }
+
+    // downhill: which way something on the slope slides, 1 for right and -1 for left
+    pub fn downhill(&self) -> f32 {
+        if self.rises_right { -1.0 } else { 1.0 }
+    }
+
+    pub fn contains_point(&self, _x: f32, _y: f32)
+        -> bool
+    {
+        match self.surface_y(_x) {
+            Some(surface) => _y > surface && _y < self.y + self.height && _x > self.x && _x < self.x + self.width,
+            None => false,
+        }
+    }
+
+    pub fn is_touching(&self, other: &RectCollider)
+        -> bool
+    {
+        self.bounding_rect().is_touching(other) && other.y() + other.height() > self.resting_y(other)
+    }
+
+    // resting_y: how low the bottom of a rectangle above the slope can go before it touches it
+    pub fn resting_y(&self, other: &RectCollider) -> f32 {
+        // the corner closest to the tall side of the triangle is the one that hits first
+        let deepest_x = if self.rises_right {
+            (other.x() + other.width()).clamp(self.x, self.x + self.width)
+        } else {
+            other.x().clamp(self.x, self.x + self.width)
+        };
+        self.surface_y(deepest_x).unwrap()
+    }
+
+    // entry_point: where a line from (from_x, from_y) to (to_x, to_y) first crosses into the triangle
+    pub fn entry_point(&self, from_x: f32, from_y: f32, to_x: f32, to_y: f32) -> Option<(f32, f32)> {
+        let bottom = self.y + self.height;
+        let (low, high) = if self.rises_right {
+            ((self.x, bottom), (self.x + self.width, self.y))
+        } else {
+            ((self.x + self.width, bottom), (self.x, self.y))
+        };
+        // the slope, the tall side and the bottom
+        let edges = [(low, high), (high, (high.0, bottom)), ((self.x, bottom), (self.x + self.width, bottom))];
+        let dx = to_x - from_x;
+        let dy = to_y - from_y;
+        let mut closest: Option<f32> = None;
+        for (a, b) in edges.iter() {
+            let ex = b.0 - a.0;
+            let ey = b.1 - a.1;
+            let denom = dx * ey - dy * ex;
+            if denom == 0.0 { continue; }
+            // how far along our line and along the edge the two cross
+            let t = ((a.0 - from_x) * ey - (a.1 - from_y) * ex) / denom;
+            let u = ((a.0 - from_x) * dy - (a.1 - from_y) * dx) / denom;
+            if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) && closest.is_none_or(|c| t < c) {
+                closest = Some(t);
+            }
+        }
+        closest.map(|t| (from_x + dx * t, from_y + dy * t))
+    }
+}